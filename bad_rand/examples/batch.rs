@@ -0,0 +1,46 @@
+//! Times the batched seed check against checking seeds one by one, on a single thread.
+//!
+//! cargo run --release --example batch -- [SEEDS]
+
+use std::{sync::Arc, time::Instant};
+
+use bad_rand::{
+    batch::Batched,
+    glibc::{GlibcRandom, StateType},
+    observation::{Mapping, Target},
+    solver::{self, Solution},
+};
+
+fn main() {
+    let seeds: u64 = std::env::args().nth(1).map_or(1 << 28, |x| x.parse().expect("SEEDS"));
+    let seed = 1_000_123;
+    let mut rng = GlibcRandom::new(seed);
+    let outputs: Vec<u32> = (0..10).map(|_| rng.rand() % 62).collect();
+    let target = Target::new(Mapping::Modulo(62), outputs).unwrap();
+
+    let pruned = match solver::solve(StateType::default(), 0, &target) {
+        Solution::Search(pruned) => Arc::new(pruned),
+        _ => unreachable!(),
+    };
+
+    let now = Instant::now();
+    let one_by_one: Vec<u64> = (1..=seeds).filter(|&x| pruned.matches(x as u32)).collect();
+    let single = now.elapsed().as_secs_f64();
+
+    let now = Instant::now();
+    let batched: Vec<u64> = Batched::new(pruned, 1..=seeds).collect();
+    let batch = now.elapsed().as_secs_f64();
+
+    assert_eq!(one_by_one, batched);
+    assert!(seeds < seed as u64 || batched.contains(&(seed as u64)));
+    let rate = |seconds: f64| seeds as f64 / seconds / 1e6;
+    println!("{} seeds, {} matches", seeds, batched.len());
+    println!("one by one: {:.2}s, {:.0}M seeds/s", single, rate(single));
+    println!("batched:    {:.2}s, {:.0}M seeds/s", batch, rate(batch));
+    println!("speedup:    {:.1}x", single / batch);
+    println!(
+        "full u32 sweep on one thread: {:.0}s -> {:.0}s",
+        single * 2f64.powi(32) / seeds as f64,
+        batch * 2f64.powi(32) / seeds as f64
+    );
+}
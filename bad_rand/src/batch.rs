@@ -0,0 +1,225 @@
+//! Seeds checked `LANES` at a time.
+//!
+//! Right after seeding, word `i` of the state is `seed * 16807^i mod (2^31 - 1)`, so the next
+//! seed only adds `16807^i` to it, and a whole batch moves on by adding `LANES * 16807^i`.
+//! That's an addition and a conditional subtraction per word instead of a modular
+//! multiplication. The words are kept lane by lane in fixed-size arrays, and the dot products
+//! of the first few constraints run over all lanes at once, which the compiler turns into SIMD
+//! instructions of whatever the target has. The few seeds that get through are checked one by
+//! one.
+//!
+//! glibc seeds with `int32_t`, so seeds from 2^31 up are negative and the words jump when the
+//! seeds cross it, the batches are started over there.
+
+use std::{collections::VecDeque, sync::Arc};
+
+use crate::glibc::{seed_state, StateType, MAX_DEGREE, MODULO, MULTIPLIER};
+
+pub const LANES: usize = 16;
+
+const MODULUS: u32 = MODULO as u32;
+
+/// Seeded states of `LANES` consecutive seeds, word by word.
+#[derive(Debug, Clone)]
+pub struct SeedStates {
+    base:   u64,
+    degree: usize,
+    words:  [[u32; LANES]; MAX_DEGREE],
+    // LANES * 16807^i mod (2^31 - 1)
+    steps:  [u32; MAX_DEGREE],
+}
+
+fn half(seed: u64) -> u64 {
+    seed >> 31
+}
+
+impl SeedStates {
+    /// States of seeds `base..base + LANES`, none of them 0 (which is seeded as 1).
+    pub fn new(ty: StateType, base: u64) -> Self {
+        assert!(!ty.is_lcg(), "TYPE_0 has a single word");
+        assert!(base != 0 && base + LANES as u64 - 1 <= u32::MAX as u64);
+
+        let degree = ty.degree();
+        let states: Vec<_> =
+            (base..base + LANES as u64).map(|seed| seed_state(ty, seed as u32)).collect();
+        let mut words = [[0; LANES]; MAX_DEGREE];
+        for (i, words) in words.iter_mut().take(degree).enumerate() {
+            for (x, state) in words.iter_mut().zip(states.iter()) {
+                *x = state[i];
+            }
+        }
+
+        let mut steps = [0; MAX_DEGREE];
+        let mut pow = 1;
+        for step in steps.iter_mut().take(degree) {
+            *step = (LANES as i64 * pow % MODULO) as u32;
+            pow = pow * MULTIPLIER % MODULO;
+        }
+        // word 0 is the seed itself
+        steps[0] = LANES as u32;
+
+        Self { base, degree, words, steps }
+    }
+
+    /// First seed of the batch.
+    pub fn base(&self) -> u64 {
+        self.base
+    }
+
+    pub fn words(&self) -> &[[u32; LANES]] {
+        &self.words[..self.degree]
+    }
+
+    /// Whether `advance()` gets to the seeds starting at `base`.
+    pub fn leads_to(&self, base: u64) -> bool {
+        let last = base + LANES as u64 - 1;
+        base == self.base + LANES as u64 && half(self.base) == half(last) && last <= u32::MAX as u64
+    }
+
+    /// Moves on to the next `LANES` seeds.
+    pub fn advance(&mut self) {
+        for lane in self.words[0].iter_mut() {
+            *lane = lane.wrapping_add(self.steps[0]);
+        }
+        for (words, &step) in self.words[1..self.degree].iter_mut().zip(self.steps[1..].iter()) {
+            for x in words.iter_mut() {
+                let sum = *x + step;
+                *x = if sum >= MODULUS { sum - MODULUS } else { sum };
+            }
+        }
+        self.base += LANES as u64;
+    }
+}
+
+/// `row · words` for every lane.
+pub fn dot(row: &[u32], words: &[[u32; LANES]]) -> [u32; LANES] {
+    let mut acc = [0u32; LANES];
+    for (&x, lanes) in row.iter().zip(words.iter()) {
+        for (acc, &y) in acc.iter_mut().zip(lanes.iter()) {
+            *acc = acc.wrapping_add(x.wrapping_mul(y));
+        }
+    }
+    acc
+}
+
+/// Per-seed check that can also run on `LANES` seeds at once.
+pub trait Check {
+    fn state_type(&self) -> StateType;
+
+    /// Lanes that may match, as a bit mask. Only those are passed to `matches()`.
+    fn lanes(&self, states: &SeedStates) -> u32;
+
+    fn matches(&self, seed: u32) -> bool;
+}
+
+/// Seeds that `check` accepts, the consecutive ones checked `LANES` at a time.
+pub struct Batched<C, I> {
+    check:  Arc<C>,
+    seeds:  I,
+    states: Option<SeedStates>,
+    found:  VecDeque<u64>,
+}
+
+impl<C: Check, I: Iterator<Item = u64>> Batched<C, I> {
+    pub fn new(check: Arc<C>, seeds: I) -> Self {
+        Self { check, seeds, states: None, found: VecDeque::new() }
+    }
+
+    fn check(&mut self, batch: &[u64]) {
+        let ty = self.check.state_type();
+        let consecutive = batch.len() == LANES
+            && batch[0] != 0
+            && batch.windows(2).all(|x| x[1] == x[0] + 1)
+            && half(batch[0]) == half(batch[LANES - 1]);
+
+        if ty.is_lcg() || !consecutive {
+            let check = &self.check;
+            self.found.extend(batch.iter().filter(|&&seed| check.matches(seed as u32)));
+            return;
+        }
+
+        let states = match self.states.take() {
+            Some(mut states) if states.leads_to(batch[0]) => {
+                states.advance();
+                states
+            }
+            _ => SeedStates::new(ty, batch[0]),
+        };
+
+        let mask = self.check.lanes(&states);
+        for (lane, &seed) in batch.iter().enumerate() {
+            if mask & 1 << lane != 0 && self.check.matches(seed as u32) {
+                self.found.push_back(seed);
+            }
+        }
+        self.states = Some(states);
+    }
+}
+
+impl<C: Check, I: Iterator<Item = u64>> Iterator for Batched<C, I> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        let mut batch = Vec::with_capacity(LANES);
+        loop {
+            if let Some(seed) = self.found.pop_front() {
+                return Some(seed);
+            }
+
+            batch.clear();
+            batch.extend(self.seeds.by_ref().take(LANES));
+            if batch.is_empty() {
+                return None;
+            }
+            self.check(&batch);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        glibc::GlibcRandom,
+        observation::{Mapping, Target},
+        solver::{self, Solution},
+    };
+
+    #[test]
+    fn incremental_states() {
+        let ty = StateType::default();
+        // crosses 2^31, where the seeds turn negative
+        let mut states = SeedStates::new(ty, (1 << 31) - 5 * LANES as u64);
+        for _ in 0..10 {
+            for lane in 0..LANES {
+                let state = seed_state(ty, (states.base() + lane as u64) as u32);
+                let words: Vec<u32> = states.words().iter().map(|x| x[lane]).collect();
+                assert_eq!(words, state[..ty.degree()]);
+            }
+
+            let base = states.base() + LANES as u64;
+            match states.leads_to(base) {
+                true => states.advance(),
+                false => states = SeedStates::new(ty, base),
+            }
+        }
+    }
+
+    #[test]
+    fn same_as_one_by_one() {
+        let mut rng = GlibcRandom::new(1_000_123);
+        let outputs: Vec<u32> = (0..6).map(|_| rng.rand() % 10).collect();
+        let target = Target::new(Mapping::Modulo(10), outputs).unwrap();
+
+        let pruned = match solver::solve(StateType::default(), 0, &target) {
+            Solution::Search(pruned) => Arc::new(pruned),
+            _ => unreachable!(),
+        };
+        let seeds = (1_000_000..1_200_000).chain(5..40).chain((1 << 31) - 1000..(1 << 31) + 1000);
+
+        let expected: Vec<u64> =
+            seeds.clone().filter(|&seed| pruned.matches(seed as u32)).collect();
+        assert!(expected.contains(&1_000_123));
+        assert_eq!(Batched::new(pruned, seeds).collect::<Vec<_>>(), expected);
+    }
+}
@@ -0,0 +1,279 @@
+//! Seeds worth trying first: the ones a program computes from the time and its PID, such as
+//! `srand(time(NULL))` or `srand(time(NULL) ^ getpid())`.
+//!
+//! Formulas are small expressions over `t` and `p` with C's operators and precedence, in
+//! `uint32_t` arithmetic: `t`, `t ^ p`, `t + (p << 16)`, `t * 1000 + 37`.
+
+use std::{fmt, ops::RangeInclusive};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expr {
+    Time,
+    Pid,
+    Number(u32),
+    Binary(Op, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Mul,
+    Add,
+    Sub,
+    Shl,
+    Shr,
+    And,
+    Xor,
+    Or,
+}
+
+impl Op {
+    // from the loosest binding, as in C
+    const LEVELS: [&'static [(&'static str, Op)]; 5] = [
+        &[("|", Op::Or)],
+        &[("^", Op::Xor)],
+        &[("&", Op::And)],
+        &[("<<", Op::Shl), (">>", Op::Shr)],
+        &[("+", Op::Add), ("-", Op::Sub)],
+    ];
+
+    fn apply(self, x: u32, y: u32) -> u32 {
+        match self {
+            Op::Mul => x.wrapping_mul(y),
+            Op::Add => x.wrapping_add(y),
+            Op::Sub => x.wrapping_sub(y),
+            Op::Shl => x.checked_shl(y).unwrap_or(0),
+            Op::Shr => x.checked_shr(y).unwrap_or(0),
+            Op::And => x & y,
+            Op::Xor => x ^ y,
+            Op::Or => x | y,
+        }
+    }
+}
+
+impl Expr {
+    fn eval(&self, time: u32, pid: u32) -> u32 {
+        match self {
+            Expr::Time => time,
+            Expr::Pid => pid,
+            Expr::Number(x) => *x,
+            Expr::Binary(op, x, y) => op.apply(x.eval(time, pid), y.eval(time, pid)),
+        }
+    }
+
+    fn uses_pid(&self) -> bool {
+        match self {
+            Expr::Pid => true,
+            Expr::Binary(_, x, y) => x.uses_pid() || y.uses_pid(),
+            _ => false,
+        }
+    }
+}
+
+// recursive descent over the precedence levels, `*` binding tightest
+struct Parser<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn eat(&mut self, token: &str) -> bool {
+        self.rest = self.rest.trim_start();
+        match self.rest.strip_prefix(token) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn level(&mut self, level: usize) -> Result<Expr, String> {
+        let next = |parser: &mut Self| match level + 1 {
+            n if n < Op::LEVELS.len() => parser.level(n),
+            _ => parser.product(),
+        };
+
+        let mut expr = next(self)?;
+        'outer: loop {
+            for &(token, op) in Op::LEVELS[level].iter() {
+                if self.eat(token) {
+                    expr = Expr::Binary(op, Box::new(expr), Box::new(next(self)?));
+                    continue 'outer;
+                }
+            }
+            return Ok(expr);
+        }
+    }
+
+    fn product(&mut self) -> Result<Expr, String> {
+        let mut expr = self.atom()?;
+        while self.eat("*") {
+            expr = Expr::Binary(Op::Mul, Box::new(expr), Box::new(self.atom()?));
+        }
+        Ok(expr)
+    }
+
+    fn atom(&mut self) -> Result<Expr, String> {
+        if self.eat("(") {
+            let expr = self.level(0)?;
+            return if self.eat(")") { Ok(expr) } else { Err("missing )".into()) };
+        }
+        if self.eat("t") {
+            return Ok(Expr::Time);
+        }
+        if self.eat("p") {
+            return Ok(Expr::Pid);
+        }
+
+        let (digits, radix) = match self.rest.strip_prefix("0x") {
+            Some(rest) => (rest, 16),
+            None => (self.rest, 10),
+        };
+        let len = digits.find(|c: char| !c.is_digit(radix)).unwrap_or(digits.len());
+        let number = u32::from_str_radix(&digits[..len], radix)
+            .map_err(|_| format!("unexpected {:?}", self.rest))?;
+        self.rest = &digits[len..];
+        Ok(Expr::Number(number))
+    }
+}
+
+/// Expression computing the seed from the time `t` and the PID `p`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Formula {
+    text: String,
+    expr: Expr,
+}
+
+impl Formula {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut parser = Parser { rest: text };
+        let expr = parser.level(0)?;
+        if !parser.rest.trim().is_empty() {
+            return Err(format!("unexpected {:?} in {:?}", parser.rest.trim(), text));
+        }
+        Ok(Self { text: text.trim().to_string(), expr })
+    }
+
+    pub fn seed(&self, time: u32, pid: u32) -> u32 {
+        self.expr.eval(time, pid)
+    }
+
+    pub fn uses_pid(&self) -> bool {
+        self.expr.uses_pid()
+    }
+}
+
+impl fmt::Display for Formula {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// Where a candidate seed came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Candidate {
+    pub seed:    u32,
+    pub time:    u32,
+    pub pid:     u32,
+    /// Index of the formula.
+    pub formula: usize,
+}
+
+/// Every formula applied to every time in the window and every PID in the range.
+#[derive(Debug, Clone)]
+pub struct SeedSource {
+    pub formulas: Vec<Formula>,
+    pub times:    RangeInclusive<u32>,
+    pub pids:     RangeInclusive<u32>,
+}
+
+/// Times handed out at once, consecutive times give consecutive seeds for most formulas.
+pub const TIMES_PER_TASK: u32 = 1 << 12;
+
+impl SeedSource {
+    /// Formulas not using the PID only run once.
+    pub fn pids(&self, formula: usize) -> RangeInclusive<u32> {
+        match self.formulas[formula].uses_pid() {
+            true => self.pids.clone(),
+            false => *self.pids.start()..=*self.pids.start(),
+        }
+    }
+
+    /// Number of candidates, duplicates included.
+    pub fn len(&self) -> u64 {
+        let times = self.times.clone().count() as u64;
+        (0..self.formulas.len()).map(|i| self.pids(i).count() as u64 * times).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Independent pieces of work: a formula, a PID and a run of times.
+    pub fn tasks(&self) -> Vec<(usize, u32, RangeInclusive<u32>)> {
+        let mut tasks = vec![];
+        for formula in 0..self.formulas.len() {
+            for pid in self.pids(formula) {
+                let mut start = *self.times.start() as u64;
+                while start <= *self.times.end() as u64 {
+                    let end = (start + TIMES_PER_TASK as u64 - 1).min(*self.times.end() as u64);
+                    tasks.push((formula, pid, start as u32..=end as u32));
+                    start = end + 1;
+                }
+            }
+        }
+        tasks
+    }
+
+    /// Candidates of a single task.
+    pub fn candidates(
+        &self,
+        (formula, pid, times): (usize, u32, RangeInclusive<u32>),
+    ) -> impl Iterator<Item = Candidate> + '_ {
+        let f = &self.formulas[formula];
+        times.map(move |time| Candidate { seed: f.seed(time, pid), time, pid, formula })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formulas() {
+        let seed = |text: &str| Formula::parse(text).unwrap().seed(1_700_000_000, 4242);
+        assert_eq!(seed("t"), 1_700_000_000);
+        assert_eq!(seed("t ^ p"), 1_700_000_000 ^ 4242);
+        assert_eq!(seed("t+p<<16"), (1_700_000_000u32 + 4242) << 16);
+        assert_eq!(seed("t + (p << 16)"), 1_700_000_000 + (4242 << 16));
+        assert_eq!(seed("t*1000 + 0x25"), 1_700_000_000u32.wrapping_mul(1000) + 37);
+        assert_eq!(seed("t ^ p & 0xff | 1"), (1_700_000_000 ^ (4242 & 0xff)) | 1);
+
+        assert!(Formula::parse("t ^").is_err());
+        assert!(Formula::parse("(t").is_err());
+        assert!(Formula::parse("t q").is_err());
+        assert!(!Formula::parse("t * 3").unwrap().uses_pid());
+    }
+
+    #[test]
+    fn enumerates_everything() {
+        let source = SeedSource {
+            formulas: vec![Formula::parse("t").unwrap(), Formula::parse("t ^ p").unwrap()],
+            times:    0..=2 * TIMES_PER_TASK,
+            pids:     100..=102,
+        };
+        let candidates: Vec<Candidate> = source
+            .tasks()
+            .into_iter()
+            .flat_map(|task| source.candidates(task).collect::<Vec<_>>())
+            .collect();
+
+        assert_eq!(candidates.len() as u64, source.len());
+        assert_eq!(source.len(), 4 * (2 * TIMES_PER_TASK as u64 + 1));
+        assert!(candidates.contains(&Candidate {
+            seed:    5 ^ 101,
+            time:    5,
+            pid:     101,
+            formula: 1,
+        }));
+    }
+}
@@ -0,0 +1,725 @@
+//! Hand-rolled argument parsing, the program is small enough not to pull in a dependency for it.
+
+use std::{ops::Range, path::PathBuf, str::FromStr};
+
+use bad_rand::{
+    candidates::{Formula, SeedSource},
+    drand48::Drand48,
+    glibc::{GlibcRandom, StateType},
+    java::JavaRandom,
+    msvc::MsvcRandom,
+    mt19937::Mt19937,
+    observation::{Mapping, Target, TargetError, DEFAULT_ALPHABET},
+    pattern::Pattern,
+    scan::Shard,
+    search::Backend,
+    weak_rng::WeakRng,
+};
+
+pub const USAGE: &str = "\
+Abuses glibc's srandom()/random() and other weak generators.
+
+USAGE:
+    bad_rand [crack] (--target <STRING> | --pattern <PATTERN> | --numbers <N,N,...>) [OPTIONS]
+    bad_rand predict --numbers <N,N,...> [--count <N>] [OPTIONS]
+    bad_rand xor --ciphertext <FILE> --plaintext <STRING> [--offset <N>] [--output <FILE>] [OPTIONS]
+    bad_rand generate --seed <N> [--length <N>] [--alphabet <STRING> | --modulus <N> | ... | --raw] [OPTIONS]
+
+COMMANDS:
+    crack                   find seeds that produce the observed outputs (default)
+    predict                 recover the state from consecutive full random() outputs, no seed needed,
+                            and print the ones that follow, glibc, mt19937 (624 outputs) and drand48
+                            (lrand48() outputs, two are enough) only
+    xor                     find the seed of a buffer encrypted with buf[i] ^= rand() & 0xff from a piece
+                            of its plaintext, and decrypt it to --output or stdout, glibc only
+    generate                print what the target program makes of a seed: the --alphabet string by
+                            default, numbers with --modulus, --scale, --high-bits or --raw
+
+OBSERVATION:
+    --rng <NAME>            generator to crack: glibc, msvc (rand()), java (java.util.Random.nextInt()),
+                            drand48 (lrand48()) or mt19937 [default: glibc]
+    --target <STRING>       characters picked as alphabet[rand() % alphabet.len()]
+    --pattern <PATTERN>     --target with ? for an unreadable character and [...] for one of a few,
+                            e.g. \"ab?d[0O]f\" or \"[a-f]xyz\", glibc only
+    --mismatches <K>        let up to K characters of --pattern be wrong, matches are ranked by how
+                            many are [default: 0]
+    --alphabet <STRING>     alphabet for --target and --pattern [default: a-zA-Z0-9]
+    --numbers <N,N,...>     outputs as numbers, rand() itself unless one of the following is given
+    --modulus <N>           numbers are rand() % N
+    --scale <N>             numbers are rand() / (RAND_MAX / N), RAND_MAX being the generator's largest output
+    --high-bits <K>         numbers are the top K bits of rand()
+
+XOR:
+    --ciphertext <FILE>     encrypted buffer
+    --plaintext <STRING>    known plaintext
+    --offset <N>            where the known plaintext starts in the buffer [default: 0]
+    --output <FILE>         where the plaintext goes [default: stdout]
+
+GENERATE:
+    --seed <N>              seed passed to srandom() or its counterpart
+    --length <N>            how many characters or numbers [default: 10]
+    --raw                   print rand() itself
+
+OPTIONS:
+    --skip <N|FROM..TO>     rand() calls made after srandom() before the first observed one [default: 0],
+                            a range searches every skip in it at once, glibc only
+    --shard <I/N>           search only the I-th of N interleaved parts of the seeds, I counting from 0
+    --first                 stop at the first match
+    --max-matches <N>       stop once N matches are found
+    --checkpoint <FILE>     record finished chunks of seeds in FILE and skip the ones already there
+    --backend <NAME>        how seeds are checked: solver, or kernel (the CUDA kernel's algorithm run on
+                            the CPU, no solver) [default: solver], glibc only, not with --pattern
+    --time <T|FROM..TO>     only try seeds computed from these Unix times, glibc only
+    --pid <N|FROM..TO>      PIDs for the formulas using p
+    --seed-formula <F,...>  how the seed is computed from the time t and the PID p, with C's operators,
+                            e.g. t, t^p or \"t + (p << 16)\" [default: t, or t,t^p with --pid]
+    --count <N>             how many outputs to predict [default: 10]
+    --before                predict the outputs made before the observed ones instead, oldest first
+    --type <0-4>            glibc state type [default: 3]
+    --state-size <BYTES>    size of the buffer passed to initstate(), instead of --type
+    --format <text|json>    [default: text]
+    -h, --help              print this message
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rng {
+    Glibc,
+    Msvc,
+    Java,
+    Drand48,
+    Mt19937,
+}
+
+impl Rng {
+    pub const ALL: [Rng; 5] = [Rng::Glibc, Rng::Msvc, Rng::Java, Rng::Drand48, Rng::Mt19937];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Rng::Glibc => GlibcRandom::NAME,
+            Rng::Msvc => MsvcRandom::NAME,
+            Rng::Java => JavaRandom::NAME,
+            Rng::Drand48 => Drand48::NAME,
+            Rng::Mt19937 => Mt19937::NAME,
+        }
+    }
+
+    fn max(self) -> u32 {
+        match self {
+            Rng::Glibc => GlibcRandom::MAX,
+            Rng::Msvc => MsvcRandom::MAX,
+            Rng::Java => JavaRandom::MAX,
+            Rng::Drand48 => Drand48::MAX,
+            Rng::Mt19937 => Mt19937::MAX,
+        }
+    }
+}
+
+/// What was seen of the outputs.
+#[derive(Debug)]
+pub enum Observed {
+    Exact(Target),
+    Pattern { pattern: Pattern, mismatches: usize },
+}
+
+#[derive(Debug)]
+pub struct CrackOptions {
+    pub rng:         Rng,
+    pub observed:    Observed,
+    pub skip:        Range<u64>,
+    pub state_type:  StateType,
+    pub shard:       Shard,
+    pub max_matches: Option<usize>,
+    pub checkpoint:  Option<PathBuf>,
+    pub backend:     Backend,
+    /// Only these seeds instead of all of them.
+    pub source:      Option<SeedSource>,
+    pub format:      Format,
+}
+
+#[derive(Debug)]
+pub struct PredictOptions {
+    pub rng:        Rng,
+    pub outputs:    Vec<u32>,
+    pub count:      usize,
+    /// The outputs preceding the observed ones instead of the following ones.
+    pub before:     bool,
+    pub state_type: StateType,
+    pub format:     Format,
+}
+
+#[derive(Debug)]
+pub struct XorOptions {
+    pub ciphertext:  PathBuf,
+    pub plaintext:   Vec<u8>,
+    pub offset:      usize,
+    /// Calls before the first byte of the buffer.
+    pub skip:        Range<u64>,
+    pub state_type:  StateType,
+    pub max_matches: Option<usize>,
+    pub source:      Option<SeedSource>,
+    pub output:      Option<PathBuf>,
+}
+
+/// What `generate` prints.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Generated {
+    Text(Vec<u8>),
+    Numbers(Mapping),
+}
+
+#[derive(Debug)]
+pub struct GenerateOptions {
+    pub rng:        Rng,
+    pub seed:       u64,
+    pub skip:       u64,
+    pub generated:  Generated,
+    pub length:     usize,
+    pub state_type: StateType,
+    pub format:     Format,
+}
+
+#[derive(Debug)]
+pub enum Command {
+    Crack(CrackOptions),
+    Predict(PredictOptions),
+    Xor(XorOptions),
+    Generate(GenerateOptions),
+}
+
+/// Flags without a value.
+const SWITCHES: [&str; 3] = ["--first", "--before", "--raw"];
+
+/// `--flag value` and `--flag=value` pairs, consumed by the command they belong to.
+struct Flags {
+    pairs: Vec<(String, String)>,
+}
+
+impl Flags {
+    fn take(&mut self, flag: &str) -> Option<String> {
+        let i = self.pairs.iter().rposition(|(x, _)| x == flag)?;
+        let value = self.pairs.remove(i).1;
+        self.pairs.retain(|(x, _)| x != flag);
+        Some(value)
+    }
+
+    fn take_number<T: FromStr>(&mut self, flag: &str) -> Result<Option<T>, String> {
+        self.take(flag).map(|value| parse_number(flag, &value)).transpose()
+    }
+
+    fn finish(self) -> Result<(), String> {
+        match self.pairs.first() {
+            Some((flag, _)) => Err(format!("unexpected option {:?}", flag)),
+            None => Ok(()),
+        }
+    }
+}
+
+fn parse_number<T: FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value.trim().parse().map_err(|_| format!("{}: invalid number {:?}", flag, value))
+}
+
+// negative numbers are taken as 32-bit two's complement, as Java's nextInt() prints them
+fn parse_numbers(flag: &str, value: &str) -> Result<Vec<u32>, String> {
+    value
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|x| !x.is_empty())
+        .map(|x| match parse_number::<i32>(flag, x) {
+            Ok(x) if x < 0 => Ok(x as u32),
+            _ => parse_number(flag, x),
+        })
+        .collect()
+}
+
+// `N` or `FROM..TO`, the end excluded
+fn parse_range(flag: &str, value: &str) -> Result<Range<u64>, String> {
+    match value.find("..") {
+        Some(i) => {
+            let range = parse_number(flag, &value[..i])?..parse_number(flag, &value[i + 2..])?;
+            if range.start >= range.end {
+                return Err(format!("{}: empty range {:?}", flag, value));
+            }
+            Ok(range)
+        }
+        None => {
+            let x: u64 = parse_number(flag, value)?;
+            let end = x.checked_add(1).ok_or_else(|| format!("{}: {} is too large", flag, x))?;
+            Ok(x..end)
+        }
+    }
+}
+
+fn parse_source(flags: &mut Flags) -> Result<Option<SeedSource>, String> {
+    let times = flags.take("--time");
+    let pids = flags.take("--pid");
+    let formulas = flags.take("--seed-formula");
+
+    let times = match times {
+        Some(times) => parse_range("--time", &times)?,
+        None if pids.is_some() || formulas.is_some() => {
+            return Err("--pid and --seed-formula need --time".into())
+        }
+        None => return Ok(None),
+    };
+    let formulas: Vec<Formula> = formulas
+        .as_deref()
+        .unwrap_or(if pids.is_some() { "t,t^p" } else { "t" })
+        .split(',')
+        .map(|x| Formula::parse(x).map_err(|e| format!("--seed-formula: {}", e)))
+        .collect::<Result<_, _>>()?;
+    let pids = match pids {
+        Some(pids) => parse_range("--pid", &pids)?,
+        None if formulas.iter().any(Formula::uses_pid) => {
+            return Err("--seed-formula: p needs --pid".into())
+        }
+        None => 0..1,
+    };
+
+    let inclusive = |flag: &str, range: Range<u64>| match range.end - 1 <= u32::MAX as u64 {
+        true => Ok(range.start as u32..=(range.end - 1) as u32),
+        false => Err(format!("{}: {} doesn't fit in 32 bits", flag, range.end - 1)),
+    };
+    Ok(Some(SeedSource {
+        formulas,
+        times: inclusive("--time", times)?,
+        pids: inclusive("--pid", pids)?,
+    }))
+}
+
+fn parse_state_type(flags: &mut Flags) -> Result<StateType, String> {
+    if let Some(size) = flags.take_number("--state-size")? {
+        return StateType::from_buffer_size(size)
+            .ok_or_else(|| "--state-size: initstate() needs at least 8 bytes".to_string());
+    }
+
+    match flags.take_number::<usize>("--type")? {
+        Some(index) => {
+            StateType::ALL.get(index).copied().ok_or_else(|| format!("--type: no TYPE_{}", index))
+        }
+        None => Ok(StateType::default()),
+    }
+}
+
+fn parse_format(flags: &mut Flags) -> Result<Format, String> {
+    match flags.take("--format").as_deref() {
+        None | Some("text") => Ok(Format::Text),
+        Some("json") => Ok(Format::Json),
+        Some(value) => Err(format!("--format: unknown format {:?}", value)),
+    }
+}
+
+fn parse_rng(flags: &mut Flags) -> Result<Rng, String> {
+    match flags.take("--rng") {
+        None => Ok(Rng::Glibc),
+        Some(value) => Rng::ALL
+            .iter()
+            .copied()
+            .find(|rng| rng.name() == value)
+            .ok_or_else(|| format!("--rng: unknown generator {:?}", value)),
+    }
+}
+
+fn parse_mapping(flags: &mut Flags) -> Result<Option<Mapping>, String> {
+    let mappings = [
+        flags.take_number("--modulus")?.map(Mapping::Modulo),
+        flags.take_number("--scale")?.map(Mapping::Scale),
+        flags.take_number("--high-bits")?.map(Mapping::HighBits),
+    ];
+    let mut mappings = mappings.iter().flatten();
+    let mapping = mappings.next().copied();
+    if mappings.next().is_some() {
+        return Err("--modulus, --scale and --high-bits are mutually exclusive".into());
+    }
+    Ok(mapping)
+}
+
+fn parse_backend(flags: &mut Flags) -> Result<Backend, String> {
+    match flags.take("--backend").as_deref() {
+        None | Some("solver") => Ok(Backend::Solver),
+        Some("kernel") => Ok(Backend::Kernel),
+        Some(value) => Err(format!("--backend: unknown backend {:?}", value)),
+    }
+}
+
+fn parse_crack(flags: &mut Flags) -> Result<CrackOptions, String> {
+    let rng = parse_rng(flags)?;
+    let text = flags.take("--target");
+    let pattern = flags.take("--pattern");
+    let mismatches = flags.take_number("--mismatches")?;
+    let alphabet = flags.take("--alphabet").map_or(DEFAULT_ALPHABET.to_vec(), String::into_bytes);
+    let numbers = flags.take("--numbers");
+    let mapping = parse_mapping(flags)?.unwrap_or(Mapping::Identity);
+
+    let observed = match (text, pattern, numbers) {
+        (Some(text), None, None) => Target::from_text(text.as_bytes(), &alphabet)
+            .and_then(|x| x.with_max(rng.max()))
+            .map(Observed::Exact),
+        (None, Some(pattern), None) => Pattern::from_text(pattern.as_bytes(), &alphabet)
+            .map(|pattern| Observed::Pattern { pattern, mismatches: mismatches.unwrap_or(0) }),
+        (None, None, Some(numbers)) => {
+            Target::for_max(mapping, parse_numbers("--numbers", &numbers)?, rng.max())
+                .map(Observed::Exact)
+        }
+        (None, None, None) => {
+            return Err("either --target, --pattern or --numbers is required".into())
+        }
+        _ => return Err("--target, --pattern and --numbers are mutually exclusive".into()),
+    }
+    .map_err(|e| e.to_string())?;
+    if mismatches.is_some() && !matches!(observed, Observed::Pattern { .. }) {
+        return Err("--mismatches needs --pattern".into());
+    }
+
+    let skip = flags.take("--skip").map_or(Ok(0..1), |value| parse_range("--skip", &value))?;
+    let state_type = parse_state_type(flags)?;
+    let shard = flags.take("--shard").map_or(Ok(Shard::default()), |value| {
+        value.parse().map_err(|e| format!("--shard: {}", e))
+    })?;
+    let checkpoint = flags.take("--checkpoint").map(PathBuf::from);
+    let max_matches = match flags.take("--first") {
+        Some(_) => Some(1),
+        None => flags.take_number("--max-matches")?,
+    };
+    let source = parse_source(flags)?;
+    let backend = parse_backend(flags)?;
+    if matches!(observed, Observed::Pattern { .. }) && skip.end - skip.start != 1 {
+        return Err("--skip: ranges don't go with --pattern".into());
+    }
+    if matches!(observed, Observed::Pattern { .. }) && backend != Backend::Solver {
+        return Err("--backend: patterns only go through the solver".into());
+    }
+    if source.is_some() && (shard != Shard::default() || checkpoint.is_some()) {
+        return Err("--shard and --checkpoint don't go with --time".into());
+    }
+
+    if rng != Rng::Glibc {
+        let options = [shard != Shard::default(), checkpoint.is_some(), max_matches.is_some()];
+        if options.contains(&true) || source.is_some() {
+            return Err(format!(
+                "--shard, --checkpoint, --first, --max-matches and --time aren't supported for {}",
+                rng.name()
+            ));
+        }
+        if skip.end - skip.start != 1 {
+            return Err(format!("--skip: ranges aren't supported for {}", rng.name()));
+        }
+        if let Observed::Pattern { .. } = observed {
+            return Err(format!("--pattern: patterns aren't supported for {}", rng.name()));
+        }
+        if state_type != StateType::default() {
+            return Err(format!("--type: {} has no state types", rng.name()));
+        }
+        if backend != Backend::Solver {
+            return Err(format!("--backend: {} has a single backend", rng.name()));
+        }
+    }
+
+    Ok(CrackOptions {
+        rng,
+        observed,
+        skip,
+        state_type,
+        shard,
+        max_matches,
+        checkpoint,
+        backend,
+        source,
+        format: parse_format(flags)?,
+    })
+}
+
+fn parse_predict(flags: &mut Flags) -> Result<PredictOptions, String> {
+    let outputs = match flags.take("--numbers") {
+        Some(numbers) => parse_numbers("--numbers", &numbers)?,
+        None => return Err("--numbers is required".into()),
+    };
+
+    let rng = parse_rng(flags)?;
+    if ![Rng::Glibc, Rng::Mt19937, Rng::Drand48].contains(&rng) {
+        return Err(format!("--rng: can't predict {} without the seed", rng.name()));
+    }
+
+    Ok(PredictOptions {
+        rng,
+        outputs,
+        count: flags.take_number("--count")?.unwrap_or(10),
+        before: flags.take("--before").is_some(),
+        state_type: parse_state_type(flags)?,
+        format: parse_format(flags)?,
+    })
+}
+
+fn parse_xor(flags: &mut Flags) -> Result<XorOptions, String> {
+    let ciphertext = flags.take("--ciphertext").ok_or("--ciphertext is required")?;
+    let plaintext = flags.take("--plaintext").ok_or("--plaintext is required")?;
+    let max_matches = match flags.take("--first") {
+        Some(_) => Some(1),
+        None => flags.take_number("--max-matches")?,
+    };
+
+    Ok(XorOptions {
+        ciphertext: PathBuf::from(ciphertext),
+        plaintext: plaintext.into_bytes(),
+        offset: flags.take_number("--offset")?.unwrap_or(0),
+        skip: flags.take("--skip").map_or(Ok(0..1), |value| parse_range("--skip", &value))?,
+        state_type: parse_state_type(flags)?,
+        max_matches,
+        source: parse_source(flags)?,
+        output: flags.take("--output").map(PathBuf::from),
+    })
+}
+
+fn parse_generate(flags: &mut Flags) -> Result<GenerateOptions, String> {
+    let rng = parse_rng(flags)?;
+    let seed = flags.take_number("--seed")?.ok_or("--seed is required")?;
+    if rng != Rng::Java && seed > u32::MAX as u64 {
+        return Err(format!("--seed: {} takes 32-bit seeds", rng.name()));
+    }
+
+    let alphabet = flags.take("--alphabet");
+    let raw = flags.take("--raw").is_some();
+    let generated = match (alphabet, parse_mapping(flags)?, raw) {
+        (alphabet, None, false) => {
+            let alphabet = alphabet.map_or(DEFAULT_ALPHABET.to_vec(), String::into_bytes);
+            if alphabet.is_empty() {
+                return Err("--alphabet: empty alphabet".into());
+            }
+            Generated::Text(alphabet)
+        }
+        (None, Some(mapping), false) if mapping.is_valid_for(rng.max()) => {
+            Generated::Numbers(mapping)
+        }
+        (None, Some(mapping), false) => {
+            return Err(TargetError::InvalidMapping(mapping).to_string())
+        }
+        (None, None, true) => Generated::Numbers(Mapping::Identity),
+        _ => {
+            return Err(
+                "--alphabet, --modulus, --scale, --high-bits and --raw are mutually exclusive"
+                    .into(),
+            )
+        }
+    };
+
+    let state_type = parse_state_type(flags)?;
+    if rng != Rng::Glibc && state_type != StateType::default() {
+        return Err(format!("--type: {} has no state types", rng.name()));
+    }
+
+    Ok(GenerateOptions {
+        rng,
+        seed,
+        skip: flags.take_number("--skip")?.unwrap_or(0),
+        generated,
+        length: flags.take_number("--length")?.unwrap_or(10),
+        state_type,
+        format: parse_format(flags)?,
+    })
+}
+
+/// `Ok(None)` means help was requested.
+pub fn parse(args: impl Iterator<Item = String>) -> Result<Option<Command>, String> {
+    let mut args = args.peekable();
+
+    let command = match args.peek() {
+        Some(arg) if !arg.starts_with('-') => args.next().unwrap(),
+        _ => "crack".to_string(),
+    };
+
+    let mut flags = Flags { pairs: vec![] };
+    while let Some(arg) = args.next() {
+        // both `--flag value` and `--flag=value`
+        let (flag, inline) = match arg.find('=') {
+            Some(i) if arg.starts_with("--") => {
+                (arg[..i].to_string(), Some(arg[i + 1..].to_string()))
+            }
+            _ => (arg, None),
+        };
+
+        if flag == "-h" || flag == "--help" {
+            return Ok(None);
+        }
+        if !flag.starts_with("--") {
+            return Err(format!("unexpected argument {:?}", flag));
+        }
+        if SWITCHES.contains(&flag.as_str()) {
+            flags.pairs.push((flag, String::new()));
+            continue;
+        }
+
+        match inline.or_else(|| args.next()) {
+            Some(value) => flags.pairs.push((flag, value)),
+            None => return Err(format!("{}: missing value", flag)),
+        }
+    }
+
+    let command = match command.as_str() {
+        "crack" => Command::Crack(parse_crack(&mut flags)?),
+        "predict" => Command::Predict(parse_predict(&mut flags)?),
+        "xor" => Command::Xor(parse_xor(&mut flags)?),
+        "generate" => Command::Generate(parse_generate(&mut flags)?),
+        _ => return Err(format!("unknown command {:?}", command)),
+    };
+    flags.finish()?;
+
+    Ok(Some(command))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_line(line: &str) -> Result<Option<Command>, String> {
+        parse(line.split_whitespace().map(String::from))
+    }
+
+    fn crack(line: &str) -> CrackOptions {
+        match parse_line(line) {
+            Ok(Some(Command::Crack(options))) => options,
+            other => panic!("{:?}", other),
+        }
+    }
+
+    fn rejects(line: &str, error: &str) {
+        match parse_line(line) {
+            Err(e) => assert!(e.contains(error), "{:?}: {:?}", line, e),
+            other => panic!("{:?}: {:?}", line, other),
+        }
+    }
+
+    #[test]
+    fn crack_defaults() {
+        let options = crack("--target abc");
+        assert_eq!(options.rng, Rng::Glibc);
+        assert!(matches!(options.observed, Observed::Exact(_)));
+        assert_eq!(options.skip, 0..1);
+        assert_eq!(options.state_type, StateType::default());
+        assert_eq!(options.shard, Shard::default());
+        assert_eq!(options.max_matches, None);
+        assert_eq!(options.backend, Backend::Solver);
+        assert!(options.source.is_none());
+        assert_eq!(options.format, Format::Text);
+
+        assert!(matches!(parse_line("crack --target abc --help"), Ok(None)));
+        assert!(matches!(parse_line("-h"), Ok(None)));
+    }
+
+    #[test]
+    fn crack_options() {
+        let options =
+            crack("crack --pattern a?[bc] --mismatches=1 --skip 3 --shard 2/4 --max-matches 5");
+        assert!(matches!(options.observed, Observed::Pattern { mismatches: 1, .. }));
+        assert_eq!(options.skip, 3..4);
+        assert_eq!(options.shard, Shard { index: 2, count: 4 });
+        assert_eq!(options.max_matches, Some(5));
+
+        let options = crack("--numbers 1,2,3 --modulus 10 --first --skip 2..9 --format json");
+        assert_eq!(options.max_matches, Some(1));
+        assert_eq!(options.skip, 2..9);
+        assert_eq!(options.format, Format::Json);
+
+        let options = crack("--rng java --numbers -1,5");
+        assert_eq!(options.rng, Rng::Java);
+
+        // the last occurrence wins
+        assert_eq!(crack("--target abc --skip 1 --skip 4").skip, 4..5);
+
+        let source = crack("--target abc --time 100..200 --pid 1..10").source.unwrap();
+        assert_eq!(source.times, 100..=199);
+        assert_eq!(source.pids, 1..=9);
+        assert_eq!(source.formulas.len(), 2);
+    }
+
+    #[test]
+    fn crack_rejects() {
+        rejects("", "either --target, --pattern or --numbers is required");
+        rejects("--target abc --numbers 1", "mutually exclusive");
+        rejects("--target abc --pattern a?c", "mutually exclusive");
+        rejects("--target abc --mismatches 1", "--mismatches needs --pattern");
+        rejects("--numbers 1 --modulus 2 --scale 3", "mutually exclusive");
+        rejects("--pattern a?c --skip 0..2", "ranges don't go with --pattern");
+        rejects("--pattern a?c --backend kernel", "only go through the solver");
+        rejects("--target abc --time 1..5 --shard 0/2", "don't go with --time");
+        rejects("--target abc --time 1..5 --checkpoint x", "don't go with --time");
+        rejects("--target abc --pid 1..5", "need --time");
+        rejects("--target abc --time 1..5 --seed-formula t^p", "p needs --pid");
+        rejects("--target abc --time 0..4294967297", "doesn't fit in 32 bits");
+        rejects("--rng msvc --numbers 1 --first", "aren't supported for msvc");
+        rejects("--rng java --numbers 1 --skip 0..3", "ranges aren't supported");
+        rejects("--rng drand48 --numbers 1 --type 1", "has no state types");
+        rejects("--rng mt19937 --numbers 1 --backend kernel", "has a single backend");
+        rejects("--rng lcg --numbers 1", "unknown generator");
+        rejects("--target abc --backend gpu", "unknown backend");
+        rejects("--target abc --format xml", "unknown format");
+        rejects("--target abc --type 5", "no TYPE_5");
+        rejects("--target abc --state-size 4", "at least 8 bytes");
+        rejects("--target abc --shard 4/4", "invalid shard");
+    }
+
+    #[test]
+    fn ranges() {
+        assert_eq!(parse_range("--skip", "5"), Ok(5..6));
+        assert_eq!(parse_range("--skip", "5..9"), Ok(5..9));
+        assert!(parse_range("--skip", "9..5").is_err());
+        assert!(parse_range("--skip", "5..5").is_err());
+        assert!(parse_range("--skip", "a..5").is_err());
+        assert_eq!(parse_range("--skip", "18446744073709551614"), Ok(u64::MAX - 1..u64::MAX));
+        rejects("--target abc --skip 18446744073709551615", "too large");
+    }
+
+    #[test]
+    fn arguments() {
+        rejects("--target", "--target: missing value");
+        rejects("--target abc stray", "unexpected argument");
+        rejects("--target abc --frobnicate 1", "unexpected option \"--frobnicate\"");
+        rejects("--target abc --skip x", "invalid number");
+        rejects("decrypt --target abc", "unknown command");
+        // options of other commands aren't taken
+        rejects("--target abc --count 3", "unexpected option \"--count\"");
+    }
+
+    #[test]
+    fn other_commands() {
+        match parse_line("predict --numbers 1,2,3 --count 4 --before --rng mt19937") {
+            Ok(Some(Command::Predict(options))) => {
+                assert_eq!(options.outputs, [1, 2, 3]);
+                assert_eq!(options.count, 4);
+                assert!(options.before);
+                assert_eq!(options.rng, Rng::Mt19937);
+            }
+            other => panic!("{:?}", other),
+        }
+        rejects("predict", "--numbers is required");
+        rejects("predict --numbers 1 --rng java", "can't predict java");
+
+        match parse_line("xor --ciphertext c.bin --plaintext hello --offset 3 --skip 1..4") {
+            Ok(Some(Command::Xor(options))) => {
+                assert_eq!(options.ciphertext, PathBuf::from("c.bin"));
+                assert_eq!(options.plaintext, b"hello");
+                assert_eq!(options.offset, 3);
+                assert_eq!(options.skip, 1..4);
+            }
+            other => panic!("{:?}", other),
+        }
+        rejects("xor --plaintext hello", "--ciphertext is required");
+        rejects("xor --ciphertext c.bin", "--plaintext is required");
+
+        match parse_line("generate --seed 7 --modulus 6 --length 3") {
+            Ok(Some(Command::Generate(options))) => {
+                assert_eq!(options.seed, 7);
+                assert_eq!(options.generated, Generated::Numbers(Mapping::Modulo(6)));
+                assert_eq!(options.length, 3);
+            }
+            other => panic!("{:?}", other),
+        }
+        rejects("generate", "--seed is required");
+        rejects("generate --seed 4294967296", "takes 32-bit seeds");
+        rejects("generate --seed 1 --raw --modulus 3", "mutually exclusive");
+        rejects("generate --seed 1 --modulus 0", "");
+    }
+}
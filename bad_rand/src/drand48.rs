@@ -0,0 +1,230 @@
+//! The `drand48()` family: the same 48-bit LCG as `java.util.Random`, seeded differently.
+//!
+//! `lrand48()` drops the low 17 bits of the state and `mrand48()` the low 16, so the state after
+//! the first output is one of 2^17 (or 2^16), and the next output tells which. `drand48()`
+//! returns the whole state as a fraction, which gives it away at once.
+
+use crate::{
+    lcg::Lcg,
+    observation::{Mapping, Target},
+    weak_rng::{self, WeakRng},
+};
+
+pub const LCG: Lcg = Lcg::new(0x5_DEEC_E66D, 0xB, 48);
+
+/// Low 16 bits of the state after `srand48()`.
+pub const SEED_LOW: u64 = 0x330E;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Drand48 {
+    state: u64,
+}
+
+impl Drand48 {
+    /// Same as `srand48(seed)`, only the low 32 bits of the seed are used.
+    pub fn new(seed: u64) -> Self {
+        Self { state: (seed & 0xFFFF_FFFF) << 16 | SEED_LOW }
+    }
+
+    pub fn from_state(state: u64) -> Self {
+        Self { state: state & LCG.mask() }
+    }
+
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    fn step(&mut self) -> u64 {
+        self.state = LCG.step(self.state);
+        self.state
+    }
+
+    /// Non-negative 31-bit number.
+    pub fn lrand48(&mut self) -> u32 {
+        (self.step() >> 17) as u32
+    }
+
+    /// Signed 32-bit number.
+    pub fn mrand48(&mut self) -> i32 {
+        (self.step() >> 16) as u32 as i32
+    }
+
+    pub fn drand48(&mut self) -> f64 {
+        self.step() as f64 / (1u64 << 48) as f64
+    }
+
+    /// Undoes the last call and returns what `lrand48()` returned for it.
+    pub fn prev_lrand48(&mut self) -> u32 {
+        let output = (self.state >> 17) as u32;
+        self.state = LCG.inverse().step(self.state);
+        output
+    }
+
+    pub fn rewind(&mut self, n: u64) {
+        self.state = LCG.inverse().pow(n).step(self.state);
+    }
+
+    /// The `srand48()` seed, if the generator got where it is `calls` calls after it.
+    pub fn seed(&self, calls: u64) -> Option<u32> {
+        let state = LCG.inverse().pow(calls).step(self.state);
+        (state & 0xFFFF == SEED_LOW).then_some((state >> 16) as u32)
+    }
+
+    /// Generators right after consecutive `lrand48()` outputs, usually a single one from two
+    /// outputs on.
+    pub fn from_lrand48(outputs: &[u32]) -> Vec<Self> {
+        let outputs: Vec<u64> = outputs.iter().map(|&x| x as u64).collect();
+        Self::from_high_bits(&outputs, 17)
+    }
+
+    /// Same as `from_lrand48()` for `mrand48()` outputs.
+    pub fn from_mrand48(outputs: &[i32]) -> Vec<Self> {
+        let outputs: Vec<u64> = outputs.iter().map(|&x| x as u32 as u64).collect();
+        Self::from_high_bits(&outputs, 16)
+    }
+
+    fn from_high_bits(outputs: &[u64], dropped: u32) -> Vec<Self> {
+        let first = match outputs.first() {
+            Some(&x) if x < 1 << (48 - dropped) => x,
+            _ => return vec![],
+        };
+
+        (0..1 << dropped)
+            .filter_map(|low| {
+                let mut rng = Self::from_state(first << dropped | low);
+                outputs[1..].iter().all(|&x| rng.step() >> dropped == x).then_some(rng)
+            })
+            .collect()
+    }
+
+    /// Generator right after consecutive `drand48()` outputs, `None` if they don't follow
+    /// from each other.
+    pub fn from_drand48(outputs: &[f64]) -> Option<Self> {
+        let mut rng = Self::from_state((outputs.first()? * (1u64 << 48) as f64) as u64);
+        outputs[1..].iter().all(|&x| rng.drand48() == x).then_some(rng)
+    }
+}
+
+impl WeakRng for Drand48 {
+    const NAME: &'static str = "drand48";
+    const MAX: u32 = i32::MAX as u32;
+
+    fn seeds() -> std::ops::RangeInclusive<u64> {
+        0..=u32::MAX as u64
+    }
+
+    fn from_seed(seed: u64) -> Self {
+        Self::new(seed)
+    }
+
+    fn next(&mut self) -> u32 {
+        self.lrand48()
+    }
+
+    fn skip(&mut self, n: u64) {
+        self.state = LCG.pow(n).step(self.state);
+    }
+
+    fn crack(skip: u64, target: &Target) -> Vec<u64> {
+        if target.mapping() != Mapping::Identity {
+            return weak_rng::brute_force::<Self>(skip, target);
+        }
+
+        // the low 16 bits srand48() sets leave a few seeds from a single output, two settle it
+        let calls = skip + target.len() as u64;
+        let mut seeds: Vec<u64> = Self::from_lrand48(target.values())
+            .iter()
+            .filter_map(|rng| rng.seed(calls))
+            .map(u64::from)
+            .collect();
+        seeds.sort_unstable();
+        seeds.dedup();
+        seeds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // produced by srand48(seed) followed by five lrand48(), then drand48() and mrand48()
+    // on glibc 2.36
+    const GOLDEN: [(u64, [u32; 5], i32, f64); 3] = [
+        (
+            1,
+            [89400484, 976015093, 1792756325, 721524505, 1214379247],
+            805690840,
+            0.001766912391744313,
+        ),
+        (
+            42,
+            [1598855263, 735945821, 238553827, 906966006, 174184913],
+            2142327205,
+            0.856440708026625,
+        ),
+        (
+            3000000000,
+            [359904366, 1032720576, 1559962378, 482838161, 1912096720],
+            1357871315,
+            0.44201003898341185,
+        ),
+    ];
+
+    #[test]
+    fn golden() {
+        for &(seed, lrand, mrand, drand) in GOLDEN.iter() {
+            let mut rng = Drand48::new(seed);
+            let outputs: Vec<u32> = (0..5).map(|_| rng.lrand48()).collect();
+            assert_eq!(outputs, lrand);
+            assert_eq!(rng.drand48(), drand);
+            assert_eq!(rng.mrand48(), mrand);
+        }
+    }
+
+    #[test]
+    fn recovers_state() {
+        let mut rng = Drand48::from_state(0x1234_5678_9ABC);
+        let lrand: Vec<u32> = (0..2).map(|_| rng.lrand48()).collect();
+        let mrand: Vec<i32> = (0..2).map(|_| rng.mrand48()).collect();
+        let drand: Vec<f64> = (0..2).map(|_| rng.drand48()).collect();
+
+        let mut expected = Drand48::from_state(0x1234_5678_9ABC);
+        expected.skip(2);
+        assert_eq!(Drand48::from_lrand48(&lrand), [expected.clone()]);
+        expected.skip(2);
+        assert_eq!(Drand48::from_mrand48(&mrand), [expected.clone()]);
+        expected.skip(2);
+        assert_eq!(Drand48::from_drand48(&drand), Some(expected));
+
+        assert_eq!(Drand48::from_lrand48(&lrand[..1]).len(), 1 << 17);
+        assert_eq!(Drand48::from_lrand48(&[lrand[0], lrand[1] ^ 1]), []);
+    }
+
+    #[test]
+    fn predicts_and_rewinds() {
+        let mut rng = Drand48::new(42);
+        let outputs: Vec<u32> = (0..100).map(|_| rng.lrand48()).collect();
+
+        let mut found = Drand48::from_lrand48(&outputs[40..43]).remove(0);
+        assert_eq!(found.seed(43), Some(42));
+        let ahead: Vec<u32> = (0..57).map(|_| found.lrand48()).collect();
+        assert_eq!(ahead, outputs[43..]);
+
+        found.rewind(57);
+        let back: Vec<u32> = (0..43).map(|_| found.prev_lrand48()).collect();
+        assert!(back.iter().eq(outputs[..43].iter().rev()));
+        assert_eq!(found, Drand48::new(42));
+    }
+
+    #[test]
+    fn recovers_seed() {
+        let mut rng = Drand48::new(3_000_000_000);
+        rng.skip(1000);
+        let values = vec![rng.lrand48(), rng.lrand48()];
+
+        let one = Target::for_max(Mapping::Identity, values[..1].to_vec(), Drand48::MAX).unwrap();
+        assert!(Drand48::crack(1000, &one).contains(&3_000_000_000));
+        let two = Target::for_max(Mapping::Identity, values, Drand48::MAX).unwrap();
+        assert_eq!(Drand48::crack(1000, &two), [3_000_000_000]);
+    }
+}
@@ -0,0 +1,298 @@
+//! Values produced by `random()` as linear combinations of the state right after seeding.
+//!
+//! The additive generators only ever add words of the state, so every produced word is a sum
+//! of the seeded words with some coefficients (mod 2^32). TYPE_0 is affine, so its state is
+//! extended with a constant 1.
+//!
+//! A call can be undone, the word it overwrote is the new one minus the rear word, and the LCG's
+//! multiplier is odd, so the transition is invertible and the same matrices step backwards.
+
+use crate::glibc::{
+    seed_state, GlibcRandom, StateType, LCG_INCREMENT, LCG_MULTIPLIER, MAX_DEGREE, RAND_MAX,
+};
+use crate::matrix::Matrix;
+
+#[derive(Debug, Clone)]
+pub struct ForwardMatrix {
+    ty:   StateType,
+    rows: Vec<Vec<u32>>,
+}
+
+impl ForwardMatrix {
+    /// Coefficients of `count` consecutive words, the first of which is produced by
+    /// `random()` call number `skip` (0-based) after `srandom()`.
+    ///
+    /// Costs O(width^3 log skip + count * width^2).
+    pub fn new(ty: StateType, skip: u64, count: usize) -> Self {
+        let transition = Self::transition(ty);
+        let jump = transition.pow(ty.discard() as u64 + skip + 1);
+        Self::from_jump(ty, &transition, &jump, count)
+    }
+
+    /// Coefficients of `count` consecutive words over the current state of a generator (see
+    /// [`input_of`](Self::input_of)), the first of which is produced by the call `offset` calls
+    /// from now. Negative offsets are calls already made, `-1` being the last of them.
+    pub fn relative(ty: StateType, offset: i64, count: usize) -> Self {
+        let transition = Self::transition(ty);
+        let jump = transition.signed_pow(offset + 1).expect("every call can be undone");
+        Self::from_jump(ty, &transition, &jump, count)
+    }
+
+    // `count` words, the first produced by the last call of `jump`
+    fn from_jump(ty: StateType, transition: &Matrix, jump: &Matrix, count: usize) -> Self {
+        let mut rows = Vec::with_capacity(count);
+        let mut row = jump.row(Self::produced(ty)).to_vec();
+        for _ in 0..count {
+            let next = transition.mul_row(&row);
+            rows.push(std::mem::replace(&mut row, next));
+        }
+
+        Self { ty, rows }
+    }
+
+    /// One `random()` call as a linear map of the window of the state,
+    /// where the window starts at the rear pointer.
+    pub fn transition(ty: StateType) -> Matrix {
+        let width = Self::width(ty);
+        let mut transition = Matrix::zero(width);
+
+        if ty.is_lcg() {
+            // window[0] is the constant, window[1] is the state
+            transition[(0, 0)] = 1;
+            transition[(1, 0)] = LCG_INCREMENT;
+            transition[(1, 1)] = LCG_MULTIPLIER;
+        } else {
+            // front word gets the rear one added, then both pointers move forward
+            let produced = Self::produced(ty);
+            (0..width - 1).for_each(|i| transition[(i, i + 1)] = 1);
+            transition[(produced, 0)] = 1;
+            transition[(width - 1, 0)] = 1;
+        }
+
+        transition
+    }
+
+    /// Position of the produced word in the window after the call.
+    fn produced(ty: StateType) -> usize {
+        if ty.is_lcg() {
+            1
+        } else {
+            ty.separation() - 1
+        }
+    }
+
+    /// Number of coefficients in a row.
+    pub fn width(ty: StateType) -> usize {
+        if ty.is_lcg() {
+            2
+        } else {
+            ty.degree()
+        }
+    }
+
+    /// Vector the rows are applied to.
+    pub fn input(ty: StateType, seed: u32) -> [u32; MAX_DEGREE] {
+        Self::window(ty, seed_state(ty, seed))
+    }
+
+    /// Vector the rows of [`relative`](Self::relative) are applied to.
+    pub fn input_of(rng: &GlibcRandom) -> [u32; MAX_DEGREE] {
+        Self::window(rng.state_type(), rng.state())
+    }
+
+    // the state as the rows see it, the LCG's extended with the constant
+    fn window(ty: StateType, state: [u32; MAX_DEGREE]) -> [u32; MAX_DEGREE] {
+        if ty.is_lcg() {
+            let mut input = [0; MAX_DEGREE];
+            input[0] = 1;
+            input[1] = state[0];
+            input
+        } else {
+            state
+        }
+    }
+
+    pub fn state_type(&self) -> StateType {
+        self.ty
+    }
+
+    pub fn rows(&self) -> &[Vec<u32>] {
+        &self.rows
+    }
+
+    /// Word number `i` for the given input.
+    pub fn raw(&self, i: usize, input: &[u32; MAX_DEGREE]) -> u32 {
+        self.rows[i]
+            .iter()
+            .zip(input.iter())
+            .fold(0u32, |acc, (x, y)| acc.wrapping_add(x.wrapping_mul(*y)))
+    }
+
+    /// Outputs of `random()` for the given seed, computed lazily so mismatches bail out early.
+    pub fn outputs(&self, seed: u32) -> impl Iterator<Item = u32> + '_ {
+        let input = Self::input(self.ty, seed);
+
+        (0..self.rows.len()).map(move |i| self.ty.output(self.raw(i, &input)))
+    }
+
+    /// Outputs of [`relative`](Self::relative) rows for the generator in its current state.
+    pub fn outputs_of(&self, rng: &GlibcRandom) -> Vec<u32> {
+        let input = Self::input_of(rng);
+
+        (0..self.rows.len()).map(|i| self.ty.output(self.raw(i, &input))).collect()
+    }
+}
+
+/// Whole state after a number of `random()` calls, for when outputs are needed at many offsets.
+#[derive(Debug, Clone)]
+pub struct Jump {
+    ty:     StateType,
+    matrix: Matrix,
+}
+
+impl Jump {
+    /// State right before `random()` call number `skip` (0-based) after `srandom()`.
+    pub fn new(ty: StateType, skip: u64) -> Self {
+        let matrix = ForwardMatrix::transition(ty).pow(ty.discard() as u64 + skip);
+        Self { ty, matrix }
+    }
+
+    /// Moves a generator by `steps` calls, backwards if negative.
+    pub fn by(ty: StateType, steps: i64) -> Self {
+        let matrix =
+            ForwardMatrix::transition(ty).signed_pow(steps).expect("every call can be undone");
+        Self { ty, matrix }
+    }
+
+    pub fn state(&self, seed: u32) -> GlibcRandom {
+        self.window(&ForwardMatrix::input(self.ty, seed))
+    }
+
+    /// Where `by()` takes `rng`, which has to be of the same type.
+    pub fn apply(&self, rng: &GlibcRandom) -> GlibcRandom {
+        assert_eq!(rng.state_type(), self.ty);
+        self.window(&ForwardMatrix::input_of(rng))
+    }
+
+    fn window(&self, input: &[u32; MAX_DEGREE]) -> GlibcRandom {
+        let mut window = [0; MAX_DEGREE];
+        window.iter_mut().zip(self.matrix.mul_vec(input)).for_each(|(x, y)| *x = y);
+
+        if self.ty.is_lcg() {
+            // drop the constant
+            window[0] = window[1] & RAND_MAX;
+            window[1] = 0;
+        }
+
+        GlibcRandom::from_state(self.ty, window)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::glibc::GlibcRandom;
+
+    #[test]
+    fn matches_emulator() {
+        for &ty in StateType::ALL.iter() {
+            for &skip in [0, 1, 30, 1000].iter() {
+                let forward = ForwardMatrix::new(ty, skip, 40);
+
+                for &seed in [1, 42, 2147483648, u32::MAX].iter() {
+                    let mut rng = GlibcRandom::with_type(ty, seed);
+                    (0..skip).for_each(|_| {
+                        rng.random();
+                    });
+                    let expected: Vec<u32> = (0..40).map(|_| rng.random()).collect();
+                    let actual: Vec<u32> = forward.outputs(seed).collect();
+
+                    assert_eq!(actual, expected, "{:?}, skip {}, seed {}", ty, skip, seed);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn jump() {
+        for &ty in StateType::ALL.iter() {
+            let mut rng = GlibcRandom::with_type(ty, 42);
+            for skip in 0..100 {
+                assert_eq!(Jump::new(ty, skip).state(42), rng, "{:?}, skip {}", ty, skip);
+                rng.random();
+            }
+        }
+    }
+
+    #[test]
+    fn steps_back() {
+        for &ty in StateType::ALL.iter() {
+            let mut rng = GlibcRandom::with_type(ty, 42);
+            let earlier: Vec<u32> = (0..50).map(|_| rng.random()).collect();
+            rng.skip(1000);
+
+            let before = ForwardMatrix::relative(ty, -1050, 50);
+            assert_eq!(before.outputs_of(&rng), earlier, "{:?}", ty);
+            let mut ahead = rng.clone();
+            let later: Vec<u32> = (0..20).map(|_| ahead.random()).collect();
+            assert_eq!(ForwardMatrix::relative(ty, 0, 20).outputs_of(&rng), later, "{:?}", ty);
+
+            let back = Jump::by(ty, -1050).apply(&rng);
+            assert_eq!(back, GlibcRandom::with_type(ty, 42), "{:?}", ty);
+
+            // way before srandom() and back
+            let steps = 1 << 40;
+            assert_eq!(
+                Jump::by(ty, steps).apply(&Jump::by(ty, -steps).apply(&rng)),
+                rng,
+                "{:?}",
+                ty
+            );
+        }
+    }
+
+    // what main used to do: run the recurrence on the coefficients themselves
+    fn stepped(ty: StateType, skip: u64, count: usize) -> Vec<Vec<u32>> {
+        let width = ForwardMatrix::width(ty);
+        let mut window = vec![vec![0u32; width]; width];
+        (0..width).for_each(|i| window[i][i] = 1);
+
+        let steps = ty.discard() as u64 + skip + count as u64;
+        let mut front = ty.separation();
+        let mut rear = 0;
+        let mut rows = vec![];
+
+        for step in 0..steps {
+            (0..width)
+                .for_each(|j| window[front][j] = window[front][j].wrapping_add(window[rear][j]));
+
+            if step >= steps - count as u64 {
+                rows.push(window[front].clone());
+            }
+
+            front = (front + 1) % width;
+            rear = (rear + 1) % width;
+        }
+
+        rows
+    }
+
+    #[test]
+    fn same_coefficients_as_stepping() {
+        for &ty in StateType::ALL.iter().filter(|ty| !ty.is_lcg()) {
+            for &skip in [0, 12_345, 100_000].iter() {
+                let forward = ForwardMatrix::new(ty, skip, 10);
+                assert_eq!(forward.rows(), &stepped(ty, skip, 10)[..], "{:?}, skip {}", ty, skip);
+            }
+        }
+    }
+
+    #[test]
+    fn huge_skip() {
+        let skip = 1_000_000_000_000_000_000;
+        let forward = ForwardMatrix::new(StateType::Type3, skip, 10);
+        let shifted = ForwardMatrix::new(StateType::Type3, skip + 1, 10);
+
+        assert_eq!(forward.rows()[1..], shifted.rows()[..9]);
+    }
+}
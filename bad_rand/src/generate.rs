@@ -0,0 +1,50 @@
+//! The cracker the other way around: what the target program makes of a given seed, to confirm
+//! a cracked seed or to build fixtures.
+
+use crate::{observation::Mapping, weak_rng::WeakRng};
+
+/// `len` outputs through `mapping`, after `skip` others.
+pub fn outputs<R: WeakRng>(mut rng: R, skip: u64, mapping: Mapping, len: usize) -> Vec<u32> {
+    rng.skip(skip);
+    (0..len).map(|_| mapping.apply_to(rng.next(), R::MAX)).collect()
+}
+
+/// String built as `alphabet[rand() % alphabet.len()]`, after `skip` other calls.
+pub fn text<R: WeakRng>(rng: R, skip: u64, alphabet: &[u8], len: usize) -> Vec<u8> {
+    let mapping = Mapping::Modulo(alphabet.len() as u32);
+    outputs(rng, skip, mapping, len).into_iter().map(|i| alphabet[i as usize]).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        glibc::{GlibcRandom, StateType},
+        msvc::MsvcRandom,
+        observation::{Target, DEFAULT_ALPHABET},
+        search::{Match, SeedSearch},
+    };
+
+    #[test]
+    fn cracks_back() {
+        // printed by a C program doing the same after srandom(seed), on glibc 2.36
+        assert_eq!(text(GlibcRandom::new(5_000_123), 0, DEFAULT_ALPHABET, 12), b"WnlOJAHhjVm1");
+        let text = text(GlibcRandom::new(1_000_042), 1000, DEFAULT_ALPHABET, 10);
+        assert_eq!(text, b"cfVtdVxHWx");
+
+        let target = Target::from_text(&text, DEFAULT_ALPHABET).unwrap();
+        let search = SeedSearch::new(StateType::default(), 1000..1001, &target)
+            .unwrap()
+            .seeds(1_000_000..=1_000_100);
+        assert_eq!(search.collect(), [Match { seed: 1_000_042, skip: 1000 }]);
+    }
+
+    #[test]
+    fn other_generators() {
+        assert_eq!(
+            outputs(MsvcRandom::from_seed(1), 0, Mapping::Identity, 4),
+            [41, 18467, 6334, 26500]
+        );
+        assert_eq!(outputs(MsvcRandom::from_seed(1), 2, Mapping::Modulo(100), 2), [34, 0]);
+    }
+}
@@ -0,0 +1,405 @@
+//! Bit-exact emulation of glibc's `srandom()`/`initstate()`/`random()`.
+
+use rayon::prelude::*;
+
+use crate::{
+    lcg::Lcg,
+    observation::Target,
+    solver::{self, Solution},
+    weak_rng::WeakRng,
+};
+
+pub const RAND_MAX: u32 = i32::MAX as u32;
+
+/// Largest state, used by TYPE_4.
+pub const MAX_DEGREE: usize = 63;
+
+pub const MODULO: i64 = i32::MAX as i64;
+pub const MULTIPLIER: i64 = 16_807;
+
+/// TYPE_0 is a plain LCG.
+pub const LCG_MULTIPLIER: u32 = 1_103_515_245;
+pub const LCG_INCREMENT: u32 = 12_345;
+
+// TYPE_0 keeps 31 bits, so it's an LCG mod 2^31
+const LCG: Lcg = Lcg::new(LCG_MULTIPLIER as u64, LCG_INCREMENT as u64, 31);
+
+/// Generator picked by `initstate()` depending on the size of the buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum StateType {
+    Type0,
+    Type1,
+    Type2,
+    // what srandom() uses without initstate()
+    #[default]
+    Type3,
+    Type4,
+}
+
+impl StateType {
+    pub const ALL: [StateType; 5] =
+        [StateType::Type0, StateType::Type1, StateType::Type2, StateType::Type3, StateType::Type4];
+
+    /// Mirrors `initstate()`: buffers shorter than 8 bytes are rejected.
+    pub fn from_buffer_size(size: usize) -> Option<Self> {
+        match size {
+            0..=7 => None,
+            8..=31 => Some(StateType::Type0),
+            32..=63 => Some(StateType::Type1),
+            64..=127 => Some(StateType::Type2),
+            128..=255 => Some(StateType::Type3),
+            _ => Some(StateType::Type4),
+        }
+    }
+
+    /// Number of words in the additive generator's state, 0 for the LCG.
+    pub fn degree(self) -> usize {
+        match self {
+            StateType::Type0 => 0,
+            StateType::Type1 => 7,
+            StateType::Type2 => 15,
+            StateType::Type3 => 31,
+            StateType::Type4 => 63,
+        }
+    }
+
+    /// Distance between front and rear pointers.
+    pub fn separation(self) -> usize {
+        match self {
+            StateType::Type0 => 0,
+            StateType::Type1 => 3,
+            StateType::Type2 => 1,
+            StateType::Type3 => 3,
+            StateType::Type4 => 1,
+        }
+    }
+
+    /// `srandom()` throws away this many outputs to mix the state.
+    pub fn discard(self) -> usize {
+        10 * self.degree()
+    }
+
+    pub fn is_lcg(self) -> bool {
+        self == StateType::Type0
+    }
+
+    /// Maps the word written to the state to what `random()` returns.
+    pub fn output(self, raw: u32) -> u32 {
+        if self.is_lcg() {
+            raw & RAND_MAX
+        } else {
+            //chucking least random bit
+            raw >> 1
+        }
+    }
+}
+
+/// State right after seeding, before the discarded outputs.
+///
+/// glibc computes `state[i] = 16807 * state[i - 1] % 2147483647` in `int32_t`,
+/// so seeds above `i32::MAX` are taken as negative numbers.
+pub fn seed_state(ty: StateType, seed: u32) -> [u32; MAX_DEGREE] {
+    let seed = if seed == 0 { 1 } else { seed };
+
+    let mut state = [seed; MAX_DEGREE];
+    let mut word = seed as i32 as i64;
+    for x in state.iter_mut().take(ty.degree()).skip(1) {
+        word = (word * MULTIPLIER).rem_euclid(MODULO);
+        *x = word as u32;
+    }
+
+    state
+}
+
+#[derive(Debug, Clone)]
+pub struct GlibcRandom {
+    ty:    StateType,
+    state: [u32; MAX_DEGREE],
+    front: usize,
+    rear:  usize,
+}
+
+// same sequence ahead, wherever the pointers are
+impl PartialEq for GlibcRandom {
+    fn eq(&self, other: &Self) -> bool {
+        let words = self.ty.degree().max(1);
+        self.ty == other.ty && self.state()[..words] == other.state()[..words]
+    }
+}
+
+impl Eq for GlibcRandom {}
+
+impl Default for GlibcRandom {
+    // glibc behaves as if srandom(1) was called before the first random()
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+impl GlibcRandom {
+    pub fn new(seed: u32) -> Self {
+        Self::with_type(StateType::default(), seed)
+    }
+
+    pub fn with_type(ty: StateType, seed: u32) -> Self {
+        let mut rng = Self { ty, state: [0; MAX_DEGREE], front: 0, rear: 0 };
+        rng.srandom(seed);
+        rng
+    }
+
+    /// Same as `initstate(seed, buf, size)`.
+    pub fn initstate(seed: u32, size: usize) -> Option<Self> {
+        StateType::from_buffer_size(size).map(|ty| Self::with_type(ty, seed))
+    }
+
+    /// Generator with the given state, starting at the rear pointer,
+    /// as if the state was copied out with `setstate()`.
+    pub fn from_state(ty: StateType, state: [u32; MAX_DEGREE]) -> Self {
+        Self { ty, state, front: ty.separation(), rear: 0 }
+    }
+
+    /// Current state, starting at the rear pointer.
+    pub fn state(&self) -> [u32; MAX_DEGREE] {
+        let mut state = self.state;
+        let degree = self.ty.degree();
+        if degree != 0 {
+            state[..degree].rotate_left(self.rear);
+        }
+        state
+    }
+
+    pub fn state_type(&self) -> StateType {
+        self.ty
+    }
+
+    pub fn srandom(&mut self, seed: u32) {
+        self.state = seed_state(self.ty, seed);
+        self.front = self.ty.separation();
+        self.rear = 0;
+
+        (0..self.ty.discard()).for_each(|_| {
+            self.random();
+        });
+    }
+
+    pub fn random(&mut self) -> u32 {
+        if self.ty.is_lcg() {
+            let value = self.state[0].wrapping_mul(LCG_MULTIPLIER).wrapping_add(LCG_INCREMENT);
+            self.state[0] = value & RAND_MAX;
+
+            return self.ty.output(value);
+        }
+
+        let value = self.state[self.front].wrapping_add(self.state[self.rear]);
+        self.state[self.front] = value;
+
+        let degree = self.ty.degree();
+        self.front = (self.front + 1) % degree;
+        self.rear = (self.rear + 1) % degree;
+
+        self.ty.output(value)
+    }
+
+    /// Undoes the last `random()` call and returns what it returned, the next `random()` returns
+    /// it again.
+    pub fn prev_random(&mut self) -> u32 {
+        if self.ty.is_lcg() {
+            let value = self.state[0];
+            self.state[0] = LCG.inverse().step(value as u64) as u32;
+
+            return value;
+        }
+
+        let degree = self.ty.degree();
+        self.front = (self.front + degree - 1) % degree;
+        self.rear = (self.rear + degree - 1) % degree;
+
+        let value = self.state[self.front];
+        self.state[self.front] = value.wrapping_sub(self.state[self.rear]);
+
+        self.ty.output(value)
+    }
+
+    /// `n` calls one by one, see [`Jump::by`](crate::forward::Jump::by) for huge `n`.
+    pub fn skip(&mut self, n: u64) {
+        (0..n).for_each(|_| {
+            self.random();
+        });
+    }
+
+    /// Goes back `n` calls one by one, see [`Jump::by`](crate::forward::Jump::by) for huge `n`.
+    pub fn rewind(&mut self, n: u64) {
+        (0..n).for_each(|_| {
+            self.prev_random();
+        });
+    }
+
+    /// glibc's `rand()` is just `random()` truncated to int.
+    pub fn rand(&mut self) -> u32 {
+        self.random()
+    }
+}
+
+/// `srandom()` with the default TYPE_3 state.
+impl WeakRng for GlibcRandom {
+    const NAME: &'static str = "glibc";
+    const MAX: u32 = RAND_MAX;
+
+    //seed 0 is the same as seed 1
+    fn seeds() -> std::ops::RangeInclusive<u64> {
+        1..=u32::MAX as u64
+    }
+
+    fn from_seed(seed: u64) -> Self {
+        Self::new(seed as u32)
+    }
+
+    fn next(&mut self) -> u32 {
+        self.random()
+    }
+
+    fn skip(&mut self, n: u64) {
+        GlibcRandom::skip(self, n)
+    }
+
+    fn crack(skip: u64, target: &Target) -> Vec<u64> {
+        let pruned = match solver::solve(StateType::default(), skip, target) {
+            Solution::Impossible(_) => return vec![],
+            Solution::Seeds(seeds) => return seeds.into_iter().map(u64::from).collect(),
+            Solution::Search(pruned) => pruned,
+        };
+
+        let mut seeds: Vec<u64> =
+            Self::seeds().into_par_iter().filter(|&seed| pruned.matches(seed as u32)).collect();
+        seeds.sort_unstable();
+        seeds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // produced by srandom(seed) followed by random() calls on glibc 2.36
+    const GOLDEN: [(u32, [u32; 8]); 6] = [
+        (
+            1,
+            [
+                1804289383, 846930886, 1681692777, 1714636915, 1957747793, 424238335, 719885386,
+                1649760492,
+            ],
+        ),
+        (
+            42,
+            [
+                71876166, 708592740, 1483128881, 907283241, 442951012, 537146758, 1366999021,
+                1854614940,
+            ],
+        ),
+        (
+            1337,
+            [
+                292616681, 1638893262, 255706927, 995816787, 588263094, 1540293802, 343418821,
+                903681492,
+            ],
+        ),
+        (
+            2147483647,
+            [
+                1065668062, 2142264300, 1066566375, 1064012770, 2141034222, 1065509725, 2135810236,
+                2139491828,
+            ],
+        ),
+        (
+            2147483648,
+            [
+                1336741213, 1210407648, 1447044896, 337392383, 82502902, 538660432, 1313908778,
+                370221063,
+            ],
+        ),
+        (
+            4294967295,
+            [
+                254925627, 1205188300, 366127624, 1401405153, 76053476, 1604170158, 1302235366,
+                362229243,
+            ],
+        ),
+    ];
+
+    // produced by initstate(seed, buf, size) followed by random() calls on glibc 2.36
+    const GOLDEN_INITSTATE: [(usize, u32, [u32; 6]); 12] = [
+        (8, 1, [1103527590, 377401575, 662824084, 1147902781, 2035015474, 368800899]),
+        (8, 42, [1250496027, 1116302264, 1000676753, 1668674806, 908095735, 71666532]),
+        (8, 2147483648, [12345, 1406932606, 654583775, 1449466924, 229283573, 1109335178]),
+        (32, 1, [964237963, 406111040, 156505215, 1274863108, 1882652865, 1405674096]),
+        (32, 42, [769798547, 2024571666, 1204852799, 931293870, 1762463907, 1056786110]),
+        (32, 2147483648, [1183231473, 667614186, 1990959771, 1946340482, 1338546766, 1815522114]),
+        (64, 1, [1894937090, 1645272306, 2143216519, 1889283008, 669383071, 354120040]),
+        (64, 42, [2051258974, 339992574, 1379825892, 1298392284, 825292997, 1282568341]),
+        (64, 2147483648, [1566802988, 1694089519, 1055793671, 1148764645, 1110324731, 1213885513]),
+        (256, 1, [510644794, 625058908, 1816371419, 326864818, 1257431873, 415485223]),
+        (256, 42, [472624893, 994493761, 100792968, 176611971, 1804504504, 774005879]),
+        (256, 2147483648, [1486258285, 697494163, 1614005767, 587142167, 954958182, 1116272038]),
+    ];
+
+    #[test]
+    fn golden() {
+        for (seed, expected) in GOLDEN.iter() {
+            let mut rng = GlibcRandom::new(*seed);
+            let actual: Vec<u32> = (0..expected.len()).map(|_| rng.random()).collect();
+
+            assert_eq!(&actual[..], &expected[..], "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn golden_initstate() {
+        for (size, seed, expected) in GOLDEN_INITSTATE.iter() {
+            let mut rng = GlibcRandom::initstate(*seed, *size).unwrap();
+            let actual: Vec<u32> = (0..expected.len()).map(|_| rng.random()).collect();
+
+            assert_eq!(&actual[..], &expected[..], "size {}, seed {}", size, seed);
+        }
+
+        assert_eq!(GlibcRandom::initstate(1, 7), None);
+        assert_eq!(GlibcRandom::initstate(42, 128), Some(GlibcRandom::new(42)));
+    }
+
+    #[test]
+    fn zero_seed_is_one() {
+        for &ty in StateType::ALL.iter() {
+            assert_eq!(GlibcRandom::with_type(ty, 0), GlibcRandom::with_type(ty, 1));
+        }
+        assert_eq!(GlibcRandom::default(), GlibcRandom::new(1));
+    }
+
+    #[test]
+    fn steps_back() {
+        for &ty in StateType::ALL.iter() {
+            let mut rng = GlibcRandom::with_type(ty, 42);
+            let start = rng.clone();
+            let outputs: Vec<u32> = (0..1000).map(|_| rng.random()).collect();
+            let end = rng.clone();
+
+            let back: Vec<u32> = (0..1000).map(|_| rng.prev_random()).collect();
+            assert!(back.iter().eq(outputs.iter().rev()), "{:?}", ty);
+            assert_eq!(rng, start, "{:?}", ty);
+
+            // past srandom() as well
+            rng.rewind(5000);
+            rng.skip(6000);
+            assert_eq!(rng, end, "{:?}", ty);
+        }
+    }
+
+    #[test]
+    fn reseed() {
+        let mut rng = GlibcRandom::new(42);
+        (0..1000).for_each(|_| {
+            rng.rand();
+        });
+        rng.srandom(1337);
+
+        assert_eq!(rng, GlibcRandom::new(1337));
+    }
+}
@@ -0,0 +1,108 @@
+//! `java.util.Random`: a 48-bit LCG, `nextInt()` returns the top 32 bits of the state.
+
+use crate::{
+    lcg::Lcg,
+    observation::{Mapping, Target},
+    weak_rng::{self, WeakRng},
+};
+
+const LCG: Lcg = Lcg::new(0x5_DEEC_E66D, 0xB, 48);
+
+/// `setSeed()` XORs the seed with the multiplier.
+const SCRAMBLER: u64 = 0x5_DEEC_E66D;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JavaRandom {
+    state: u64,
+}
+
+impl JavaRandom {
+    /// Same as `new Random(seed)`, only the low 48 bits of the seed matter.
+    pub fn new(seed: u64) -> Self {
+        Self { state: (seed ^ SCRAMBLER) & LCG.mask() }
+    }
+
+    /// The seed that gives `state` right after seeding.
+    pub fn seed_of(state: u64) -> u64 {
+        (state ^ SCRAMBLER) & LCG.mask()
+    }
+
+    pub fn next_bits(&mut self, bits: u32) -> u32 {
+        self.state = LCG.step(self.state);
+        (self.state >> (48 - bits)) as u32
+    }
+
+    /// `nextInt()` as an unsigned number.
+    pub fn next_int(&mut self) -> u32 {
+        self.next_bits(32)
+    }
+
+    /// States that continue with `values` of `nextInt()`, from the 16 bits the first one drops.
+    fn states(values: &[u32]) -> impl Iterator<Item = u64> + '_ {
+        (0..1 << 16).map(move |low| (values[0] as u64) << 16 | low).filter(move |&state| {
+            let mut rng = Self { state };
+            values[1..].iter().all(|&x| rng.next_int() == x)
+        })
+    }
+}
+
+impl WeakRng for JavaRandom {
+    const NAME: &'static str = "java";
+    const MAX: u32 = u32::MAX;
+
+    /// Only seeds that fit in 32 bits, a full 48-bit seed is recovered from two `nextInt()`s.
+    fn seeds() -> std::ops::RangeInclusive<u64> {
+        0..=u32::MAX as u64
+    }
+
+    fn from_seed(seed: u64) -> Self {
+        Self::new(seed)
+    }
+
+    fn next(&mut self) -> u32 {
+        self.next_int()
+    }
+
+    fn skip(&mut self, n: u64) {
+        self.state = LCG.pow(n).step(self.state);
+    }
+
+    fn crack(skip: u64, target: &Target) -> Vec<u64> {
+        if target.mapping() != Mapping::Identity || target.len() < 2 {
+            return weak_rng::brute_force::<Self>(skip, target);
+        }
+
+        // the states are right after the first output, back to the one after seeding
+        let back = LCG.inverse().pow(skip + 1);
+        let mut seeds: Vec<u64> =
+            Self::states(target.values()).map(|state| Self::seed_of(back.step(state))).collect();
+        seeds.sort_unstable();
+        seeds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn golden() {
+        let outputs = |seed| {
+            let mut rng = JavaRandom::new(seed);
+            (0..4).map(|_| rng.next_int() as i32).collect::<Vec<_>>()
+        };
+        assert_eq!(outputs(42), [-1170105035, 234785527, -1360544799, 205897768]);
+        assert_eq!(outputs(0), [-1155484576, -723955400, 1033096058, -1690734402]);
+    }
+
+    #[test]
+    fn recovers_seed() {
+        let seed = 0xDEAD_BEEF_CAFE;
+        let mut rng = JavaRandom::new(seed);
+        rng.skip(1000);
+        let values = vec![rng.next_int(), rng.next_int()];
+
+        let target = Target::for_max(Mapping::Identity, values, u32::MAX).unwrap();
+        assert_eq!(JavaRandom::crack(1000, &target), [seed]);
+    }
+}
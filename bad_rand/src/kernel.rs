@@ -0,0 +1,194 @@
+//! The brute force of `bad_rand_cuda` on the CPU, so the two can be checked against each other.
+//!
+//! The kernel gives every thread an equal slice of the seeds. For each seed it computes the
+//! seeded words straight from a table of `16807^i mod (2^31 - 1)`, then applies the rows of the
+//! forward matrix, kept flat and row-major, one output at a time until one doesn't match. Here
+//! the slices are the chunks of the scan, walked `BLOCK` seeds at a time with the words of a
+//! block stored word by word, so each row goes over the whole block while it's in cache.
+//!
+//! The kernel takes seeds as unsigned, which gets every seed from 2^31 up wrong as glibc seeds
+//! with `int32_t`, and it doesn't turn seed 0 into 1. Both are done as glibc does them here. It
+//! also only knows TYPE_3 and `rand() % 62`, this takes any state type and target.
+//!
+//! As in the kernel, where the target sits among the rows is a parameter, so a range of skips
+//! is a single matrix with the target tried at every offset.
+
+use std::{collections::VecDeque, ops::Range, sync::Arc};
+
+use crate::{
+    forward::ForwardMatrix,
+    glibc::{StateType, MODULO, MULTIPLIER},
+    observation::Target,
+};
+
+/// Seeds checked together.
+pub const BLOCK: usize = 256;
+
+/// Seeds producing the target after any of the skips, evaluated as the kernel does.
+#[derive(Debug, Clone)]
+pub struct KernelCheck {
+    ty:      StateType,
+    skips:   Range<u64>,
+    width:   usize,
+    // rows of the forward matrix one after the other
+    forward: Vec<u32>,
+    // 16807^i mod (2^31 - 1)
+    pows:    Vec<i64>,
+    target:  Target,
+}
+
+impl KernelCheck {
+    pub fn new(ty: StateType, skips: Range<u64>, target: Target) -> Self {
+        assert!(!skips.is_empty());
+        let width = ForwardMatrix::width(ty);
+        let count = (skips.end - skips.start) as usize + target.len() - 1;
+        let forward = ForwardMatrix::new(ty, skips.start, count).rows().concat();
+
+        let mut pows = vec![1i64; width];
+        for i in 1..width {
+            pows[i] = pows[i - 1] * MULTIPLIER % MODULO;
+        }
+
+        Self { ty, skips, width, forward, pows, target }
+    }
+
+    /// Word `i` of the vector the rows are applied to, see [`ForwardMatrix::input`].
+    fn word(&self, seed: u32, i: usize) -> u32 {
+        let seed = if seed == 0 { 1 } else { seed };
+        match i {
+            0 if self.ty.is_lcg() => 1,
+            _ if self.ty.is_lcg() => seed,
+            0 => seed,
+            _ => (seed as i32 as i64 * self.pows[i]).rem_euclid(MODULO) as u32,
+        }
+    }
+
+    fn admits(&self, i: usize, raw: u32) -> bool {
+        self.target.mapping().apply(self.ty.output(raw)) == self.target.values()[i]
+    }
+
+    /// Rows of the target when it comes after `skip` calls.
+    fn rows(&self, skip: u64) -> impl Iterator<Item = &[u32]> {
+        let begin = (skip - self.skips.start) as usize * self.width;
+        self.forward[begin..].chunks_exact(self.width).take(self.target.len())
+    }
+
+    /// The kernel's loop for a single seed and skip.
+    pub fn matches(&self, seed: u32, skip: u64) -> bool {
+        assert!(self.skips.contains(&skip));
+        let words: Vec<u32> = (0..self.width).map(|i| self.word(seed, i)).collect();
+
+        self.rows(skip).enumerate().all(|(i, row)| {
+            let raw = row
+                .iter()
+                .zip(words.iter())
+                .fold(0u32, |acc, (&x, &y)| acc.wrapping_add(x.wrapping_mul(y)));
+            self.admits(i, raw)
+        })
+    }
+
+    /// Appends the `(seed, skip)` matches among `seeds` to `found`, `words` holding at least
+    /// `width * BLOCK` words.
+    fn block(&self, seeds: &[u64], words: &mut [u32], found: &mut Vec<(u64, u64)>) {
+        assert!(seeds.len() <= BLOCK);
+        let n = seeds.len();
+
+        // words[i * BLOCK + k] is word i of seed k
+        for i in 0..self.width {
+            let words = &mut words[i * BLOCK..i * BLOCK + n];
+            for (x, &seed) in words.iter_mut().zip(seeds.iter()) {
+                *x = self.word(seed as u32, i);
+            }
+        }
+
+        for skip in self.skips.clone() {
+            let mut alive: Vec<usize> = (0..n).collect();
+            for (i, row) in self.rows(skip).enumerate() {
+                let mut raw = [0u32; BLOCK];
+                for (j, &x) in row.iter().enumerate() {
+                    for (acc, &y) in raw.iter_mut().zip(words[j * BLOCK..j * BLOCK + n].iter()) {
+                        *acc = acc.wrapping_add(x.wrapping_mul(y));
+                    }
+                }
+
+                alive.retain(|&k| self.admits(i, raw[k]));
+                if alive.is_empty() {
+                    break;
+                }
+            }
+            found.extend(alive.iter().map(|&k| (seeds[k], skip)));
+        }
+    }
+}
+
+/// `(seed, skip)` matches of `check`, `BLOCK` seeds at a time.
+pub struct Blocks<I> {
+    check: Arc<KernelCheck>,
+    seeds: I,
+    words: Vec<u32>,
+    found: VecDeque<(u64, u64)>,
+}
+
+impl<I: Iterator<Item = u64>> Blocks<I> {
+    pub fn new(check: Arc<KernelCheck>, seeds: I) -> Self {
+        let words = vec![0; check.width * BLOCK];
+        Self { check, seeds, words, found: VecDeque::new() }
+    }
+}
+
+impl<I: Iterator<Item = u64>> Iterator for Blocks<I> {
+    type Item = (u64, u64);
+
+    fn next(&mut self) -> Option<(u64, u64)> {
+        let mut found = vec![];
+        while self.found.is_empty() {
+            let seeds: Vec<u64> = self.seeds.by_ref().take(BLOCK).collect();
+            if seeds.is_empty() {
+                return None;
+            }
+            self.check.block(&seeds, &mut self.words, &mut found);
+            found.sort_unstable();
+            self.found.extend(found.drain(..));
+        }
+        self.found.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{glibc::GlibcRandom, observation::Mapping};
+
+    #[test]
+    fn same_words_as_srandom() {
+        let seeds = [0, 1, 42, (1 << 31) - 1, 1 << 31, (1 << 31) + 1, u32::MAX];
+        for &ty in StateType::ALL.iter() {
+            let target = Target::new(Mapping::Identity, vec![0]).unwrap();
+            let check = KernelCheck::new(ty, 0..1, target);
+            for &seed in seeds.iter() {
+                let words: Vec<u32> = (0..check.width).map(|i| check.word(seed, i)).collect();
+                let input = ForwardMatrix::input(ty, seed);
+                assert_eq!(words, input[..check.width], "{:?}, seed {}", ty, seed);
+            }
+        }
+    }
+
+    #[test]
+    fn blocks_agree_with_single_seeds() {
+        // weak enough a target that a few seeds of each block get through
+        let mut rng = GlibcRandom::with_type(StateType::Type1, 2_000_100);
+        rng.skip(20);
+        let values = (0..2).map(|_| rng.random() % 4).collect();
+        let target = Target::new(Mapping::Modulo(4), values).unwrap();
+
+        let check = Arc::new(KernelCheck::new(StateType::Type1, 18..22, target));
+        let seeds = 2_000_000..2_001_000;
+        let expected: Vec<(u64, u64)> = seeds
+            .clone()
+            .flat_map(|x| (18..22).map(move |skip| (x, skip)))
+            .filter(|&(x, skip)| check.matches(x as u32, skip))
+            .collect();
+        assert!(expected.contains(&(2_000_100, 20)));
+        assert_eq!(Blocks::new(check, seeds).collect::<Vec<_>>(), expected);
+    }
+}
@@ -0,0 +1,79 @@
+//! Truncated linear congruential generators: `x = a * x + c mod 2^bits`.
+//!
+//! Steps compose into another affine map, so jumping by any number of steps costs `O(log n)`,
+//! and the multiplier is odd, so they can be undone.
+
+use crate::matrix::inverse_odd;
+
+/// `x -> multiplier * x + increment (mod 2^bits)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lcg {
+    pub multiplier: u64,
+    pub increment:  u64,
+    pub bits:       u32,
+}
+
+impl Lcg {
+    pub const fn new(multiplier: u64, increment: u64, bits: u32) -> Self {
+        Self { multiplier, increment, bits }
+    }
+
+    pub fn mask(self) -> u64 {
+        u64::MAX >> (64 - self.bits)
+    }
+
+    pub fn step(self, x: u64) -> u64 {
+        self.multiplier.wrapping_mul(x).wrapping_add(self.increment) & self.mask()
+    }
+
+    /// `self` applied after `other`.
+    pub fn then(self, other: Self) -> Self {
+        Self {
+            multiplier: self.multiplier.wrapping_mul(other.multiplier) & self.mask(),
+            increment:  self.step(other.increment),
+            bits:       self.bits,
+        }
+    }
+
+    /// `n` steps at once, by repeated squaring.
+    pub fn pow(self, mut n: u64) -> Self {
+        let mut result = Self::new(1, 0, self.bits);
+        let mut square = self;
+        while n != 0 {
+            if n & 1 == 1 {
+                result = square.then(result);
+            }
+            square = square.then(square);
+            n >>= 1;
+        }
+        result
+    }
+
+    /// The step going backwards.
+    pub fn inverse(self) -> Self {
+        let multiplier = inverse_odd(self.multiplier) & self.mask();
+        Self {
+            multiplier,
+            increment: multiplier.wrapping_mul(self.increment).wrapping_neg() & self.mask(),
+            bits: self.bits,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JAVA: Lcg = Lcg::new(0x5_DEEC_E66D, 0xB, 48);
+
+    #[test]
+    fn pow_and_inverse() {
+        let mut x = 0x1234_5678_9ABC;
+        for _ in 0..1000 {
+            x = JAVA.step(x);
+        }
+        assert_eq!(JAVA.pow(1000).step(0x1234_5678_9ABC), x);
+        assert_eq!(JAVA.inverse().pow(1000).step(x), 0x1234_5678_9ABC);
+        assert_eq!(JAVA.inverse().then(JAVA), Lcg::new(1, 0, 48));
+    }
+}
@@ -0,0 +1,21 @@
+pub mod batch;
+pub mod candidates;
+pub mod drand48;
+pub mod forward;
+pub mod generate;
+pub mod glibc;
+pub mod java;
+pub mod kernel;
+pub mod lcg;
+pub mod matrix;
+pub mod msvc;
+pub mod mt19937;
+pub mod observation;
+pub mod pattern;
+pub mod predict;
+pub mod scan;
+pub mod search;
+pub mod sliding;
+pub mod solver;
+pub mod weak_rng;
+pub mod xor;
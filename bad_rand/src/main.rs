@@ -1,51 +1,471 @@
-use rayon::prelude::*;
+mod cli;
 
-fn main() {
-    const TARGET: &[u8; 10] = b"NLXGI4NoAp";
-    const ALPHABET: &[u8; 62] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
-    const FORWARD_BY: usize = 100_000_000 + 310;
-    const STATE_SIZE: usize = 31;
-    const MODULO: i64 = i32::MAX as i64;
+use std::{collections::BTreeMap, io::Write};
+
+use bad_rand::{
+    candidates::{Candidate, SeedSource},
+    drand48::Drand48,
+    generate,
+    glibc::GlibcRandom,
+    java::JavaRandom,
+    msvc::MsvcRandom,
+    mt19937::{self, Mt19937},
+    observation::Mapping,
+    predict::Predictor,
+    scan::Checkpoint,
+    search::{Match, SeedSearch},
+    weak_rng::WeakRng,
+    xor::{self, KnownPlaintext},
+};
+use cli::{
+    Command, CrackOptions, Format, GenerateOptions, Generated, Observed, PredictOptions, Rng,
+    XorOptions,
+};
+
+fn fail(message: impl std::fmt::Display) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1);
+}
 
-    let mut pows: [i64; STATE_SIZE] = [1; STATE_SIZE];
-    (1..STATE_SIZE).for_each(|i| pows[i] = (pows[i - 1] * 16_807) % MODULO);
-    let pows = pows;
+fn open_checkpoint(options: &CrackOptions) -> Option<Checkpoint> {
+    // everything that decides the matches, resuming with anything else would mix them up
+    let description = format!(
+        "type={} skip={:?} shard={} target={:?}",
+        options.state_type as usize, options.skip, options.shard, options.observed
+    );
 
-    let mut forward_matrix = [[0u32; STATE_SIZE]; STATE_SIZE];
-    (0..STATE_SIZE).for_each(|i| forward_matrix[i][i] = 1);
+    options.checkpoint.as_ref().map(|path| {
+        let checkpoint = Checkpoint::open(path, &description).unwrap_or_else(|e| fail(e));
+        if checkpoint.done() != 0 {
+            eprintln!("resuming, {} chunks already done", checkpoint.done());
+        }
+        checkpoint
+    })
+}
 
-    let mut begin = 3;
-    let mut end = 0;
+fn join<T: ToString>(values: &[T]) -> String {
+    values.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(",")
+}
 
-    for _ in 0..FORWARD_BY {
-        (0..STATE_SIZE).for_each(|j| {
-            forward_matrix[begin][j] = forward_matrix[begin][j].wrapping_add(forward_matrix[end][j])
-        });
-        begin = (begin + 1) % STATE_SIZE;
-        end = (end + 1) % STATE_SIZE;
+fn print_seeds<T: ToString>(options: &CrackOptions, seeds: &[T]) {
+    match options.format {
+        Format::Text => seeds.iter().for_each(|seed| println!("{}", seed.to_string())),
+        Format::Json if options.rng == Rng::Glibc => println!(
+            "{{\"type\":{},\"skip\":{},\"seeds\":[{}]}}",
+            options.state_type as usize,
+            options.skip.start,
+            join(seeds)
+        ),
+        Format::Json => println!(
+            "{{\"rng\":\"{}\",\"skip\":{},\"seeds\":[{}]}}",
+            options.rng.name(),
+            options.skip.start,
+            join(seeds)
+        ),
     }
+}
 
-    (1u32..=std::u32::MAX).into_par_iter().for_each(|seed| {
-        if seed % 100_000_000 == 0 {
-            println!("{:?}: {}M", std::thread::current().id(), seed / 1_000_000);
+fn print_matches(options: &CrackOptions, matches: &[Match]) {
+    match options.format {
+        Format::Text => matches.iter().for_each(|x| println!("{} {}", x.seed, x.skip)),
+        Format::Json => {
+            let matches: Vec<String> = matches
+                .iter()
+                .map(|x| format!("{{\"seed\":{},\"skip\":{}}}", x.seed, x.skip))
+                .collect();
+            println!(
+                "{{\"type\":{},\"skip\":[{},{}],\"matches\":[{}]}}",
+                options.state_type as usize,
+                options.skip.start,
+                options.skip.end,
+                matches.join(",")
+            );
         }
+    }
+}
 
-        for i in 0..TARGET.len() {
-            let coeffs = &forward_matrix[(begin + i) - TARGET.len()];
-            let first_part = coeffs[0].wrapping_mul(((seed as i32 as i64) * pows[0]) as u32);
+fn print_candidates(options: &CrackOptions, source: &SeedSource, matches: &[(Candidate, u64)]) {
+    // a seed usually comes from many times and PIDs, `t ^ p` alone gives one per time
+    let mut origins: BTreeMap<(u32, u64), Vec<Candidate>> = BTreeMap::new();
+    for &(x, skip) in matches {
+        origins.entry((x.seed, skip)).or_default().push(x);
+    }
 
-            let sum: u32 = coeffs
+    // the PID means nothing to formulas not using it
+    let origin = |x: &Candidate, json: bool| {
+        let formula = &source.formulas[x.formula];
+        match (formula.uses_pid(), json) {
+            (true, false) => format!("t={} p={} {}", x.time, x.pid, formula),
+            (false, false) => format!("t={} {}", x.time, formula),
+            (true, true) => {
+                format!("{{\"time\":{},\"pid\":{},\"formula\":\"{}\"}}", x.time, x.pid, formula)
+            }
+            (false, true) => format!("{{\"time\":{},\"formula\":\"{}\"}}", x.time, formula),
+        }
+    };
+
+    match options.format {
+        Format::Text => {
+            for ((seed, skip), origins) in origins.iter() {
+                match origins.len() {
+                    1 => println!("{} {} {}", seed, skip, origin(&origins[0], false)),
+                    n => println!(
+                        "{} {} {} and {} more",
+                        seed,
+                        skip,
+                        origin(&origins[0], false),
+                        n - 1
+                    ),
+                }
+            }
+        }
+        Format::Json => {
+            let matches: Vec<String> = origins
+                .iter()
+                .map(|((seed, skip), origins)| {
+                    let origins: Vec<String> = origins.iter().map(|x| origin(x, true)).collect();
+                    format!(
+                        "{{\"seed\":{},\"skip\":{},\"origins\":[{}]}}",
+                        seed,
+                        skip,
+                        origins.join(",")
+                    )
+                })
+                .collect();
+            println!(
+                "{{\"type\":{},\"skip\":[{},{}],\"matches\":[{}]}}",
+                options.state_type as usize,
+                options.skip.start,
+                options.skip.end,
+                matches.join(",")
+            );
+        }
+    }
+}
+
+fn print_ranked(options: &CrackOptions, ranked: &[(Match, usize)]) {
+    match options.format {
+        Format::Text => {
+            ranked.iter().for_each(|(x, mismatches)| println!("{} {}", x.seed, mismatches))
+        }
+        Format::Json => {
+            let matches: Vec<String> = ranked
                 .iter()
-                .zip(pows.iter())
-                .skip(1)
-                .map(|(x, y)| x.wrapping_mul((((seed as i64) * y) % MODULO) as u32))
-                .fold(first_part, |acc, x| acc.wrapping_add(x));
+                .map(|(x, mismatches)| {
+                    format!("{{\"seed\":{},\"mismatches\":{}}}", x.seed, mismatches)
+                })
+                .collect();
+            println!(
+                "{{\"type\":{},\"skip\":{},\"matches\":[{}]}}",
+                options.state_type as usize,
+                options.skip.start,
+                matches.join(",")
+            );
+        }
+    }
+}
+
+fn crack_weak<R: WeakRng>(options: &CrackOptions) {
+    let target = match &options.observed {
+        Observed::Exact(target) => target,
+        Observed::Pattern { .. } => unreachable!("patterns are turned down for {}", R::NAME),
+    };
+    print_seeds(options, &R::crack(options.skip.start, target));
+}
+
+fn crack(options: &CrackOptions) {
+    match options.rng {
+        Rng::Glibc => {}
+        Rng::Msvc => return crack_weak::<MsvcRandom>(options),
+        Rng::Java => return crack_weak::<JavaRandom>(options),
+        Rng::Drand48 => return crack_weak::<Drand48>(options),
+        Rng::Mt19937 => return crack_weak::<Mt19937>(options),
+    }
+
+    let search = match &options.observed {
+        Observed::Exact(target) => SeedSearch::with_backend(
+            options.state_type,
+            options.skip.clone(),
+            target,
+            options.backend,
+        )
+        .unwrap_or_else(|e| fail(format!("no seed can produce the target: {}", e))),
+        Observed::Pattern { pattern, mismatches } => SeedSearch::with_pattern(
+            options.state_type,
+            options.skip.start,
+            pattern.clone(),
+            *mismatches,
+        ),
+    };
+    let mut search = search.shard(options.shard);
+    if let Some(max) = options.max_matches {
+        search = search.max_matches(max);
+    }
+    if search.facts() != 0 {
+        eprintln!("{} bits of the seeded state are pinned down by carries", search.facts());
+    }
+
+    if let Some(source) = &options.source {
+        eprintln!("trying {} candidates", source.len());
+        return print_candidates(options, source, &search.run_source(source));
+    }
+
+    let checkpoint = if search.is_solved() { None } else { open_checkpoint(options) };
+    let matches = search
+        .run(checkpoint.as_ref(), |done, total| eprintln!("{}/{} chunks done", done, total))
+        .unwrap_or_else(|e| fail(format!("can't write the checkpoint: {}", e)));
+
+    if let Observed::Pattern { .. } = options.observed {
+        print_ranked(options, &search.rank(&matches));
+    } else if options.skip.end - options.skip.start == 1 {
+        let seeds: Vec<u32> = matches.iter().map(|x| x.seed).collect();
+        print_seeds(options, &seeds);
+    } else {
+        print_matches(options, &matches);
+    }
+}
+
+fn predict_mt19937(options: &PredictOptions) {
+    let mut rng = match Mt19937::from_outputs(&options.outputs) {
+        Some(rng) => rng,
+        None if options.outputs.len() < mt19937::N => fail(format!(
+            "{} consecutive outputs are needed, got {}",
+            mt19937::N,
+            options.outputs.len()
+        )),
+        None => fail(format!("outputs after the first {} don't follow from them", mt19937::N)),
+    };
+    let outputs: Vec<u32> = if options.before {
+        rng.rewind(options.outputs.len() as u64);
+        let mut outputs: Vec<u32> = (0..options.count).map(|_| rng.prev_word()).collect();
+        outputs.reverse();
+        outputs
+    } else {
+        (0..options.count).map(|_| rng.next_u32()).collect()
+    };
+
+    let seed = Mt19937::recover_seed(0, &options.outputs);
+    if let Some(seed) = seed {
+        eprintln!("the outputs start right after init_genrand({})", seed);
+    }
+    print_prediction(options, seed, &outputs);
+}
+
+fn predict_drand48(options: &PredictOptions) {
+    let mut rngs = Drand48::from_lrand48(&options.outputs);
+    let mut rng = match rngs.len() {
+        1 => rngs.remove(0),
+        0 => fail("the outputs don't follow from each other"),
+        n => fail(format!("{} states fit the outputs, one more output settles it", n)),
+    };
+
+    let seed = rng.seed(options.outputs.len() as u64);
+    if let Some(seed) = seed {
+        eprintln!("the outputs start right after srand48({})", seed);
+    }
+
+    let outputs: Vec<u32> = if options.before {
+        rng.rewind(options.outputs.len() as u64);
+        let mut outputs: Vec<u32> = (0..options.count).map(|_| rng.prev_lrand48()).collect();
+        outputs.reverse();
+        outputs
+    } else {
+        (0..options.count).map(|_| rng.lrand48()).collect()
+    };
+    print_prediction(options, seed, &outputs);
+}
+
+fn print_prediction(options: &PredictOptions, seed: Option<u32>, outputs: &[u32]) {
+    match options.format {
+        Format::Text => outputs.iter().for_each(|x| println!("{}", x)),
+        Format::Json => println!(
+            "{{\"rng\":\"{}\",\"seed\":{},\"outputs\":[{}]}}",
+            options.rng.name(),
+            seed.map_or("null".to_string(), |x| x.to_string()),
+            join(outputs)
+        ),
+    }
+}
+
+fn predict(options: &PredictOptions) {
+    match options.rng {
+        Rng::Mt19937 => return predict_mt19937(options),
+        Rng::Drand48 => return predict_drand48(options),
+        _ => {}
+    }
+
+    let mut predictor = Predictor::new(options.state_type);
+    let mut rejected = vec![];
+
+    for &x in options.outputs.iter() {
+        if let Err(e) = predictor.push(x) {
+            eprintln!("{}, ignoring it", e);
+            rejected.push(e.index);
+        }
+    }
 
-            if TARGET[i] != ALPHABET[(sum >> 1) as usize % ALPHABET.len()] {
+    let outputs = match predictor.state() {
+        Some(mut rng) if options.before => {
+            rng.rewind(options.outputs.len() as u64);
+            let mut outputs: Vec<u32> = (0..options.count).map(|_| rng.prev_random()).collect();
+            outputs.reverse();
+            outputs
+        }
+        Some(mut rng) => (0..options.count).map(|_| rng.random()).collect(),
+        None => fail(format!(
+            "not enough outputs to recover the state, {} consistent so far",
+            predictor.outputs().len()
+        )),
+    };
+
+    match options.format {
+        Format::Text => outputs.iter().for_each(|x| println!("{}", x)),
+        Format::Json => println!(
+            "{{\"type\":{},\"rejected\":[{}],\"outputs\":[{}]}}",
+            options.state_type as usize,
+            join(&rejected),
+            join(&outputs)
+        ),
+    }
+}
+
+fn decrypt(options: &XorOptions) {
+    let ciphertext = std::fs::read(&options.ciphertext)
+        .unwrap_or_else(|e| fail(format!("can't read {}: {}", options.ciphertext.display(), e)));
+    let known = KnownPlaintext {
+        ciphertext: &ciphertext,
+        plaintext:  &options.plaintext,
+        offset:     options.offset,
+    };
+
+    let mut search =
+        known.search(options.state_type, options.skip.clone()).unwrap_or_else(|e| fail(e));
+    if let Some(max) = options.max_matches {
+        search = search.max_matches(max);
+    }
+    let matches: Vec<Match> = match &options.source {
+        Some(source) => {
+            let mut matches: Vec<Match> = search
+                .run_source(source)
+                .into_iter()
+                .map(|(x, skip)| Match { seed: x.seed, skip })
+                .collect();
+            matches.dedup();
+            matches
+        }
+        None => search
+            .run(None, |done, total| eprintln!("{}/{} chunks done", done, total))
+            .unwrap_or_else(|e| fail(e)),
+    };
+
+    let found = match matches.first() {
+        Some(&found) => found,
+        None => fail("no seed encrypts the plaintext at that offset"),
+    };
+    for &x in matches.iter() {
+        eprintln!("seed {} skip {}", x.seed, known.skip(x));
+    }
+    if matches.len() > 1 {
+        eprintln!("decrypting with the first one");
+    }
+
+    let plaintext = xor::decrypt(options.state_type, found.seed, known.skip(found), &ciphertext);
+    let written = match &options.output {
+        Some(path) => std::fs::write(path, plaintext),
+        None => std::io::stdout().write_all(&plaintext),
+    };
+    written.unwrap_or_else(|e| fail(format!("can't write the plaintext: {}", e)));
+}
+
+// JSON string for text that may hold quotes or any other byte
+fn json_string(text: &[u8]) -> String {
+    let mut escaped = String::from("\"");
+    for c in String::from_utf8_lossy(text).chars() {
+        match c {
+            '"' | '\\' => escaped.extend(['\\', c]),
+            c if (c as u32) < 0x20 => escaped += &format!("\\u{:04x}", c as u32),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+fn emulate_with<R: WeakRng>(options: &GenerateOptions, rng: R) {
+    let (key, value) = match &options.generated {
+        Generated::Text(alphabet) => {
+            let text = generate::text(rng, options.skip, alphabet, options.length);
+            if options.format == Format::Text {
+                let mut stdout = std::io::stdout();
+                let written = stdout.write_all(&text).and_then(|_| stdout.write_all(b"\n"));
+                written.unwrap_or_else(|e| fail(format!("can't write the text: {}", e)));
                 return;
             }
+            ("text", json_string(&text))
+        }
+        Generated::Numbers(mapping) => {
+            let outputs = generate::outputs(rng, options.skip, *mapping, options.length);
+            // as Java prints them
+            let outputs: Vec<String> = match options.rng {
+                Rng::Java if *mapping == Mapping::Identity => {
+                    outputs.iter().map(|&x| (x as i32).to_string()).collect()
+                }
+                _ => outputs.iter().map(u32::to_string).collect(),
+            };
+            if options.format == Format::Text {
+                println!("{}", outputs.join(","));
+                return;
+            }
+            ("outputs", format!("[{}]", outputs.join(",")))
+        }
+    };
+
+    match options.rng {
+        Rng::Glibc => println!(
+            "{{\"type\":{},\"seed\":{},\"skip\":{},\"{}\":{}}}",
+            options.state_type as usize, options.seed, options.skip, key, value
+        ),
+        rng => println!(
+            "{{\"rng\":\"{}\",\"seed\":{},\"skip\":{},\"{}\":{}}}",
+            rng.name(),
+            options.seed,
+            options.skip,
+            key,
+            value
+        ),
+    }
+}
+
+fn emulate(options: &GenerateOptions) {
+    let seed = options.seed;
+    match options.rng {
+        Rng::Glibc => {
+            emulate_with(options, GlibcRandom::with_type(options.state_type, seed as u32))
         }
+        Rng::Msvc => emulate_with(options, MsvcRandom::from_seed(seed)),
+        Rng::Java => emulate_with(options, JavaRandom::from_seed(seed)),
+        Rng::Drand48 => emulate_with(options, Drand48::from_seed(seed)),
+        Rng::Mt19937 => emulate_with(options, Mt19937::from_seed(seed)),
+    }
+}
 
-        println!("\n{:?} found: {}\n", std::thread::current().id(), seed);
-    });
+fn main() {
+    let command = match cli::parse(std::env::args().skip(1)) {
+        Ok(Some(command)) => command,
+        Ok(None) => {
+            print!("{}", cli::USAGE);
+            return;
+        }
+        Err(e) => {
+            eprintln!("error: {}\n\nsee --help", e);
+            std::process::exit(2);
+        }
+    };
+
+    match command {
+        Command::Crack(options) => crack(&options),
+        Command::Predict(options) => predict(&options),
+        Command::Xor(options) => decrypt(&options),
+        Command::Generate(options) => emulate(&options),
+    }
 }
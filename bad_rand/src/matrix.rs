@@ -0,0 +1,200 @@
+//! Square matrices over Z/2^32.
+
+use std::ops::{Index, IndexMut, Mul};
+
+use crate::mt19937::Word;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Matrix {
+    size: usize,
+    data: Vec<u32>,
+}
+
+impl Matrix {
+    pub fn zero(size: usize) -> Self {
+        Self { size, data: vec![0; size * size] }
+    }
+
+    pub fn identity(size: usize) -> Self {
+        let mut matrix = Self::zero(size);
+        (0..size).for_each(|i| matrix[(i, i)] = 1);
+        matrix
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn row(&self, i: usize) -> &[u32] {
+        &self.data[i * self.size..(i + 1) * self.size]
+    }
+
+    /// `self * column`
+    pub fn mul_vec(&self, column: &[u32]) -> Vec<u32> {
+        (0..self.size)
+            .map(|i| {
+                self.row(i)
+                    .iter()
+                    .zip(column.iter())
+                    .fold(0u32, |acc, (x, y)| acc.wrapping_add(x.wrapping_mul(*y)))
+            })
+            .collect()
+    }
+
+    /// `row * self`
+    pub fn mul_row(&self, row: &[u32]) -> Vec<u32> {
+        let mut result = vec![0u32; self.size];
+
+        for (i, &x) in row.iter().enumerate().filter(|(_, &x)| x != 0) {
+            for (acc, &y) in result.iter_mut().zip(self.row(i)) {
+                *acc = acc.wrapping_add(x.wrapping_mul(y));
+            }
+        }
+
+        result
+    }
+
+    /// Repeated squaring, O(size^3 log n).
+    pub fn pow(&self, mut n: u64) -> Self {
+        let mut result = Self::identity(self.size);
+        let mut base = self.clone();
+
+        while n != 0 {
+            if n & 1 == 1 {
+                result = &result * &base;
+            }
+            n >>= 1;
+            if n != 0 {
+                base = &base * &base;
+            }
+        }
+
+        result
+    }
+
+    /// `pow()` going backwards for negative `n`, `None` if the matrix isn't invertible then.
+    pub fn signed_pow(&self, n: i64) -> Option<Self> {
+        match n {
+            0.. => Some(self.pow(n as u64)),
+            _ => Some(self.inverse()?.pow(n.unsigned_abs())),
+        }
+    }
+
+    /// Gauss-Jordan elimination, pivots have to be odd to be invertible mod 2^32.
+    /// `None` if the determinant is even.
+    pub fn inverse(&self) -> Option<Self> {
+        let n = self.size;
+        let mut lhs = self.clone();
+        let mut rhs = Self::identity(n);
+
+        for col in 0..n {
+            let pivot = (col..n).find(|&i| lhs[(i, col)] & 1 == 1)?;
+            lhs.swap_rows(col, pivot);
+            rhs.swap_rows(col, pivot);
+
+            let inv = inverse_odd(lhs[(col, col)]);
+            for j in 0..n {
+                lhs[(col, j)] = lhs[(col, j)].wrapping_mul(inv);
+                rhs[(col, j)] = rhs[(col, j)].wrapping_mul(inv);
+            }
+
+            for i in (0..n).filter(|&i| i != col) {
+                let factor = lhs[(i, col)];
+                if factor == 0 {
+                    continue;
+                }
+                for j in 0..n {
+                    lhs[(i, j)] = lhs[(i, j)].wrapping_sub(factor.wrapping_mul(lhs[(col, j)]));
+                    rhs[(i, j)] = rhs[(i, j)].wrapping_sub(factor.wrapping_mul(rhs[(col, j)]));
+                }
+            }
+        }
+
+        Some(rhs)
+    }
+
+    fn swap_rows(&mut self, i: usize, j: usize) {
+        for k in 0..self.size {
+            self.data.swap(i * self.size + k, j * self.size + k);
+        }
+    }
+}
+
+/// Multiplicative inverse of an odd word mod 2^bits, by Newton's iteration.
+pub fn inverse_odd<W: Word>(x: W) -> W {
+    let one = W::from_index(1);
+    debug_assert!(x & one == one);
+
+    // correct to 3 bits, every iteration doubles that
+    let (mut inv, mut bits) = (x, 3);
+    while bits < W::BITS {
+        inv = inv.wrapping_mul(W::from_index(2).wrapping_sub(x.wrapping_mul(inv)));
+        bits *= 2;
+    }
+    inv
+}
+
+impl Index<(usize, usize)> for Matrix {
+    type Output = u32;
+
+    fn index(&self, (i, j): (usize, usize)) -> &u32 {
+        &self.data[i * self.size + j]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Matrix {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut u32 {
+        &mut self.data[i * self.size + j]
+    }
+}
+
+impl Mul for &Matrix {
+    type Output = Matrix;
+
+    fn mul(self, rhs: &Matrix) -> Matrix {
+        assert_eq!(self.size, rhs.size);
+
+        let data = (0..self.size).flat_map(|i| rhs.mul_row(self.row(i))).collect();
+        Matrix { size: self.size, data }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pow_is_repeated_mul() {
+        let mut matrix = Matrix::zero(5);
+        (0..25).for_each(|i| matrix[(i / 5, i % 5)] = (i as u32).wrapping_mul(2_654_435_761));
+
+        let mut expected = Matrix::identity(5);
+        for n in 0..70 {
+            assert_eq!(matrix.pow(n), expected, "n = {}", n);
+            expected = &expected * &matrix;
+        }
+    }
+
+    #[test]
+    fn inverse() {
+        for x in (1..1_000_000u32).step_by(2).chain(Some(u32::MAX)) {
+            assert_eq!(x.wrapping_mul(inverse_odd(x)), 1, "{}", x);
+        }
+        for x in (1..1_000_000u64).step_by(2).map(|x| x.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1) {
+            assert_eq!(x.wrapping_mul(inverse_odd(x)), 1, "{}", x);
+        }
+
+        let mut matrix = Matrix::identity(4);
+        (0..4).for_each(|i| matrix[(i, (i + 1) % 4)] = 3 + i as u32);
+        let inverse = matrix.inverse().unwrap();
+        assert_eq!(&matrix * &inverse, Matrix::identity(4));
+        assert_eq!(&inverse * &matrix, Matrix::identity(4));
+
+        assert_eq!(&matrix.signed_pow(-5).unwrap() * &matrix.pow(7), &matrix * &matrix);
+
+        let mut singular = Matrix::identity(3);
+        singular[(0, 0)] = 2;
+        assert_eq!(singular.inverse(), None);
+        assert_eq!(singular.signed_pow(-1), None);
+    }
+}
@@ -0,0 +1,60 @@
+//! Microsoft C runtime's `srand()`/`rand()`: a 32-bit LCG returning bits 16..30.
+
+use crate::{lcg::Lcg, weak_rng::WeakRng};
+
+pub const RAND_MAX: u32 = 0x7FFF;
+
+const LCG: Lcg = Lcg::new(214_013, 2_531_011, 32);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MsvcRandom {
+    state: u32,
+}
+
+impl MsvcRandom {
+    pub fn new(seed: u32) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn rand(&mut self) -> u32 {
+        self.state = LCG.step(self.state as u64) as u32;
+        (self.state >> 16) & RAND_MAX
+    }
+}
+
+impl WeakRng for MsvcRandom {
+    const NAME: &'static str = "msvc";
+    const MAX: u32 = RAND_MAX;
+
+    fn seeds() -> std::ops::RangeInclusive<u64> {
+        0..=u32::MAX as u64
+    }
+
+    fn from_seed(seed: u64) -> Self {
+        Self::new(seed as u32)
+    }
+
+    fn next(&mut self) -> u32 {
+        self.rand()
+    }
+
+    fn skip(&mut self, n: u64) {
+        self.state = LCG.pow(n).step(self.state as u64) as u32;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn golden() {
+        let mut rng = MsvcRandom::new(1);
+        let outputs: Vec<u32> = (0..8).map(|_| rng.rand()).collect();
+        assert_eq!(outputs, [41, 18467, 6334, 26500, 19169, 15724, 11478, 29358]);
+
+        let mut rng = MsvcRandom::new(1);
+        rng.skip(5);
+        assert_eq!(rng.rand(), 15724);
+    }
+}
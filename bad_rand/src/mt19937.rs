@@ -0,0 +1,465 @@
+//! Mersenne Twister, MT19937 and MT19937-64, as in the reference implementations and C++'s
+//! `std::mt19937`/`std::mt19937_64`.
+//!
+//! Outputs are tempered state words and tempering is invertible, so N consecutive outputs give
+//! the whole state. The state is kept as the last N words of the sequence
+//! `x[k + N] = x[k + M] ^ twist(upper bit of x[k] | lower bits of x[k + 1])`, which is what the
+//! reference implementation computes in place, one block at a time. `twist()` shifts right by one
+//! and adds `A` when the dropped bit was set, so the top bit of the result tells whether it was,
+//! and `x[k]` can be recovered from the N words after it: the upper bit from the equation for
+//! `x[k + N]` and the lower ones from the equation for `x[k + N - 1]`. That is how the generator
+//! steps backwards. Words set by `init_genrand()` don't follow the recurrence, so stepping back
+//! to the seeded state loses the lower bits of the seed itself, it's recovered from the next word.
+
+use std::{
+    fmt::Debug,
+    ops::{BitAnd, BitOr, BitXor, Not, Shl, Shr},
+};
+
+use crate::{
+    matrix::inverse_odd,
+    observation::{Mapping, Target},
+    weak_rng::{self, WeakRng},
+};
+
+pub trait Word:
+    Copy
+    + Eq
+    + Debug
+    + BitXor<Output = Self>
+    + BitAnd<Output = Self>
+    + BitOr<Output = Self>
+    + Shl<u32, Output = Self>
+    + Shr<u32, Output = Self>
+    + Not<Output = Self>
+{
+    const BITS: u32;
+
+    fn from_index(i: usize) -> Self;
+    fn wrapping_add(self, other: Self) -> Self;
+    fn wrapping_sub(self, other: Self) -> Self;
+    fn wrapping_mul(self, other: Self) -> Self;
+}
+
+impl Word for u32 {
+    const BITS: u32 = 32;
+
+    fn from_index(i: usize) -> Self {
+        i as u32
+    }
+
+    fn wrapping_add(self, other: Self) -> Self {
+        self.wrapping_add(other)
+    }
+
+    fn wrapping_sub(self, other: Self) -> Self {
+        self.wrapping_sub(other)
+    }
+
+    fn wrapping_mul(self, other: Self) -> Self {
+        self.wrapping_mul(other)
+    }
+}
+
+impl Word for u64 {
+    const BITS: u32 = 64;
+
+    fn from_index(i: usize) -> Self {
+        i as u64
+    }
+
+    fn wrapping_add(self, other: Self) -> Self {
+        self.wrapping_add(other)
+    }
+
+    fn wrapping_sub(self, other: Self) -> Self {
+        self.wrapping_sub(other)
+    }
+
+    fn wrapping_mul(self, other: Self) -> Self {
+        self.wrapping_mul(other)
+    }
+}
+
+/// Constants of a Mersenne Twister, named as in the paper.
+pub trait Params: Clone + Debug + PartialEq + Eq {
+    type Word: Word;
+
+    /// Degree of recurrence.
+    const N: usize;
+    const M: usize;
+    /// Twist matrix.
+    const A: Self::Word;
+    /// Bits taken from `x[k]`, the rest comes from `x[k + 1]`.
+    const UPPER_MASK: Self::Word;
+    const U: u32;
+    const D: Self::Word;
+    const S: u32;
+    const B: Self::Word;
+    const T: u32;
+    const C: Self::Word;
+    const L: u32;
+    /// Initialization multiplier.
+    const F: Self::Word;
+    const DEFAULT_SEED: Self::Word;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mt32;
+
+impl Params for Mt32 {
+    type Word = u32;
+
+    const N: usize = 624;
+    const M: usize = 397;
+    const A: u32 = 0x9908_B0DF;
+    const UPPER_MASK: u32 = 0x8000_0000;
+    const U: u32 = 11;
+    const D: u32 = 0xFFFF_FFFF;
+    const S: u32 = 7;
+    const B: u32 = 0x9D2C_5680;
+    const T: u32 = 15;
+    const C: u32 = 0xEFC6_0000;
+    const L: u32 = 18;
+    const F: u32 = 1_812_433_253;
+    const DEFAULT_SEED: u32 = 5489;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mt64;
+
+impl Params for Mt64 {
+    type Word = u64;
+
+    const N: usize = 312;
+    const M: usize = 156;
+    const A: u64 = 0xB502_6F5A_A966_19E9;
+    const UPPER_MASK: u64 = 0xFFFF_FFFF_8000_0000;
+    const U: u32 = 29;
+    const D: u64 = 0x5555_5555_5555_5555;
+    const S: u32 = 17;
+    const B: u64 = 0x71D6_7FFF_EDA6_0000;
+    const T: u32 = 37;
+    const C: u64 = 0xFFF7_EEE0_0000_0000;
+    const L: u32 = 43;
+    const F: u64 = 6_364_136_223_846_793_005;
+    const DEFAULT_SEED: u64 = 5489;
+}
+
+pub type Mt19937 = Twister<Mt32>;
+pub type Mt19937_64 = Twister<Mt64>;
+
+pub const N: usize = Mt32::N;
+
+#[derive(Debug, Clone)]
+pub struct Twister<P: Params> {
+    // x[k - N..k], x[j] lives at j % N
+    words: Vec<P::Word>,
+    // k % N
+    index: usize,
+}
+
+// same sequence ahead, wherever the ring starts
+impl<P: Params> PartialEq for Twister<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.words() == other.words()
+    }
+}
+
+impl<P: Params> Eq for Twister<P> {}
+
+impl<P: Params> Default for Twister<P> {
+    fn default() -> Self {
+        Self::new(P::DEFAULT_SEED)
+    }
+}
+
+pub fn temper<P: Params>(x: P::Word) -> P::Word {
+    let mut y = x ^ (x >> P::U & P::D);
+    y = y ^ (y << P::S & P::B);
+    y = y ^ (y << P::T & P::C);
+    y ^ y >> P::L
+}
+
+pub fn untemper<P: Params>(y: P::Word) -> P::Word {
+    let x = unshift_right(y, P::L, !P::Word::from_index(0));
+    let x = unshift_left(x, P::T, P::C);
+    let x = unshift_left(x, P::S, P::B);
+    unshift_right(x, P::U, P::D)
+}
+
+// inverse of `x ^ (x >> shift & mask)`, every iteration fixes `shift` more bits
+fn unshift_right<W: Word>(y: W, shift: u32, mask: W) -> W {
+    (0..W::BITS / shift).fold(y, |x, _| y ^ (x >> shift & mask))
+}
+
+fn unshift_left<W: Word>(y: W, shift: u32, mask: W) -> W {
+    (0..W::BITS / shift).fold(y, |x, _| y ^ (x << shift & mask))
+}
+
+fn twist<P: Params>(upper: P::Word, lower: P::Word) -> P::Word {
+    let y = (upper & P::UPPER_MASK) | (lower & !P::UPPER_MASK);
+    let mag = if y & P::Word::from_index(1) == P::Word::from_index(1) {
+        P::A
+    } else {
+        P::Word::from_index(0)
+    };
+    y >> 1 ^ mag
+}
+
+impl<P: Params> Twister<P> {
+    /// Same as `init_genrand(seed)`.
+    pub fn new(seed: P::Word) -> Self {
+        let mut words = vec![seed; P::N];
+        for i in 1..P::N {
+            words[i] = P::F
+                .wrapping_mul(words[i - 1] ^ words[i - 1] >> (P::Word::BITS - 2))
+                .wrapping_add(P::Word::from_index(i));
+        }
+        Self { words, index: 0 }
+    }
+
+    /// State that continues with the given raw words, oldest first.
+    pub fn from_words(words: &[P::Word]) -> Self {
+        assert_eq!(words.len(), P::N);
+        Self { words: words.to_vec(), index: 0 }
+    }
+
+    /// State right after all of `outputs`, recovered from the first N of them, `None` if there
+    /// are fewer or the rest don't follow from them.
+    pub fn from_outputs(outputs: &[P::Word]) -> Option<Self> {
+        if outputs.len() < P::N {
+            return None;
+        }
+
+        let words: Vec<P::Word> = outputs[..P::N].iter().map(|&y| untemper::<P>(y)).collect();
+        let mut rng = Self::from_words(&words);
+        outputs[P::N..].iter().all(|&y| rng.next_word() == y).then_some(rng)
+    }
+
+    /// Raw words the next outputs are computed from, oldest first.
+    pub fn words(&self) -> Vec<P::Word> {
+        let mut words = self.words.clone();
+        words.rotate_left(self.index);
+        words
+    }
+
+    /// The seed, if the generator is right after `init_genrand()`.
+    ///
+    /// Only the upper bits of the first word matter for the outputs, so the seed is taken from
+    /// `words[1] = F * (seed ^ seed >> (w - 2)) + 1`, the xorshift being its own inverse.
+    pub fn seed(&self) -> Option<P::Word> {
+        let words = self.words();
+        let mixed = inverse_odd(P::F).wrapping_mul(words[1].wrapping_sub(P::Word::from_index(1)));
+        let seed = mixed ^ mixed >> (P::Word::BITS - 2);
+
+        let seeded = Self::new(seed).words;
+        let upper = P::UPPER_MASK;
+        (seeded[1..] == words[1..] && seeded[0] & upper == words[0] & upper).then_some(seed)
+    }
+
+    /// Same as `genrand_int32()`/`genrand64_int64()`.
+    pub fn next_word(&mut self) -> P::Word {
+        let (n, k) = (P::N, self.index);
+        let x = self.words[(k + P::M) % n] ^ twist::<P>(self.words[k], self.words[(k + 1) % n]);
+        self.words[k] = x;
+        self.index = (k + 1) % n;
+        temper::<P>(x)
+    }
+
+    /// Steps back one output and returns it, the next `next_word()` returns it again.
+    pub fn prev_word(&mut self) -> P::Word {
+        let n = P::N;
+        let last = (self.index + n - 1) % n;
+        let word = |i: usize| self.words[i % n];
+        let output = temper::<P>(word(last));
+
+        // x[last] = x[last + M] ^ twist(x[old] | x[old + 1]) for the word x[old] it replaced
+        let upper = untwist::<P>(word(last) ^ word(last + P::M));
+        // x[last - 1] = x[last - 1 + M] ^ twist(x[old - 1] | x[old])
+        let lower = untwist::<P>(word(last + n - 1) ^ word(last + n - 1 + P::M));
+
+        self.words[last] = (upper & P::UPPER_MASK) | (lower & !P::UPPER_MASK);
+        self.index = last;
+        output
+    }
+
+    pub fn skip(&mut self, n: u64) {
+        (0..n).for_each(|_| {
+            self.next_word();
+        });
+    }
+
+    pub fn rewind(&mut self, n: u64) {
+        (0..n).for_each(|_| {
+            self.prev_word();
+        });
+    }
+
+    /// Seed of the generator that produced `outputs` after `skip` others, if it was seeded
+    /// by `init_genrand()`.
+    pub fn recover_seed(skip: u64, outputs: &[P::Word]) -> Option<P::Word> {
+        let mut rng = Self::from_outputs(outputs)?;
+        rng.rewind(skip + outputs.len() as u64);
+        rng.seed()
+    }
+}
+
+// y that `twist()` turned into `x`
+fn untwist<P: Params>(x: P::Word) -> P::Word {
+    let top = P::Word::from_index(1) << (P::Word::BITS - 1);
+    if x & top == top {
+        (x ^ P::A) << 1 | P::Word::from_index(1)
+    } else {
+        x << 1
+    }
+}
+
+impl Mt19937 {
+    /// Same as `genrand_int32()`.
+    pub fn next_u32(&mut self) -> u32 {
+        self.next_word()
+    }
+}
+
+impl Mt19937_64 {
+    /// Same as `genrand64_int64()`.
+    pub fn next_u64(&mut self) -> u64 {
+        self.next_word()
+    }
+}
+
+impl WeakRng for Mt19937 {
+    const NAME: &'static str = "mt19937";
+    const MAX: u32 = u32::MAX;
+
+    fn seeds() -> std::ops::RangeInclusive<u64> {
+        0..=u32::MAX as u64
+    }
+
+    fn from_seed(seed: u64) -> Self {
+        Self::new(seed as u32)
+    }
+
+    fn next(&mut self) -> u32 {
+        self.next_u32()
+    }
+
+    fn skip(&mut self, n: u64) {
+        Twister::skip(self, n);
+    }
+
+    fn crack(skip: u64, target: &Target) -> Vec<u64> {
+        if target.mapping() != Mapping::Identity || target.len() < N {
+            return weak_rng::brute_force::<Self>(skip, target);
+        }
+
+        Self::recover_seed(skip, target.values()).map(u64::from).into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // produced by std::mt19937 with libstdc++
+    const GOLDEN: [(u32, [u32; 5]); 3] = [
+        (5489, [3499211612, 581869302, 3890346734, 3586334585, 545404204]),
+        (1, [1791095845, 4282876139, 3093770124, 4005303368, 491263]),
+        (42, [1608637542, 3421126067, 4083286876, 787846414, 3143890026]),
+    ];
+
+    // produced by std::mt19937_64 with libstdc++
+    const GOLDEN_64: [(u64, [u64; 4]); 3] = [
+        (
+            5489,
+            [14514284786278117030, 4620546740167642908, 13109570281517897720, 17462938647148434322],
+        ),
+        (1, [2469588189546311528, 2516265689700432462, 8323445853463659930, 387828560950575246]),
+        (
+            42,
+            [13930160852258120406, 11788048577503494824, 13874630024467741450, 2513787319205155662],
+        ),
+    ];
+
+    #[test]
+    fn golden() {
+        for &(seed, expected) in GOLDEN.iter() {
+            let mut rng = Mt19937::new(seed);
+            let outputs: Vec<u32> = (0..5).map(|_| rng.next_u32()).collect();
+            assert_eq!(outputs, expected);
+        }
+        for &(seed, expected) in GOLDEN_64.iter() {
+            let mut rng = Mt19937_64::new(seed);
+            let outputs: Vec<u64> = (0..4).map(|_| rng.next_u64()).collect();
+            assert_eq!(outputs, expected);
+        }
+
+        // the standard requires the 10000th output of default-constructed generators
+        let mut rng = Mt19937::default();
+        rng.skip(9999);
+        assert_eq!(rng.next_u32(), 4_123_659_995);
+
+        let mut rng = Mt19937_64::default();
+        rng.skip(9999);
+        assert_eq!(rng.next_u64(), 9_981_545_732_273_789_042);
+    }
+
+    #[test]
+    fn untemper_inverts_temper() {
+        for &x in [0, 1, 0xDEAD_BEEF, u32::MAX].iter() {
+            assert_eq!(untemper::<Mt32>(temper::<Mt32>(x)), x);
+        }
+        for &x in [0, 1, 0xDEAD_BEEF_CAFE_BABE, u64::MAX].iter() {
+            assert_eq!(untemper::<Mt64>(temper::<Mt64>(x)), x);
+        }
+    }
+
+    #[test]
+    fn predicts_and_rewinds() {
+        let mut rng = Mt19937::new(42);
+        rng.skip(1000);
+        let outputs: Vec<u32> = (0..N + 700).map(|_| rng.next_u32()).collect();
+
+        let mut cloned = Mt19937::from_outputs(&outputs[..N]).unwrap();
+        let predicted: Vec<u32> = (0..700).map(|_| cloned.next_u32()).collect();
+        assert_eq!(predicted, outputs[N..]);
+
+        let rewound: Vec<u32> = (0..N + 700).map(|_| cloned.prev_word()).collect();
+        assert_eq!(rewound.into_iter().rev().collect::<Vec<_>>(), outputs);
+        assert_eq!(Mt19937::from_outputs(&outputs), Some(rng));
+
+        let mut rng = Mt19937_64::new(7);
+        let outputs: Vec<u64> = (0..Mt64::N * 2).map(|_| rng.next_u64()).collect();
+        let mut cloned = Mt19937_64::from_outputs(&outputs[Mt64::N..]).unwrap();
+        cloned.rewind(outputs.len() as u64);
+        assert_eq!(cloned.seed(), Some(7));
+    }
+
+    #[test]
+    fn continues_after_every_output() {
+        let mut rng = Mt19937::new(5489);
+        let mut outputs: Vec<u32> = (0..N + 10).map(|_| rng.next_u32()).collect();
+        let next = rng.next_u32();
+
+        let mut cloned = Mt19937::from_outputs(&outputs).unwrap();
+        assert_eq!(cloned.next_u32(), next);
+        assert!(Mt19937::from_outputs(&outputs[..N - 1]).is_none());
+
+        outputs[N + 3] ^= 1;
+        assert!(Mt19937::from_outputs(&outputs).is_none());
+    }
+
+    #[test]
+    fn recovers_seed() {
+        let mut rng = Mt19937::new(3_141_592_653);
+        rng.skip(12_345);
+        let outputs: Vec<u32> = (0..N).map(|_| rng.next_u32()).collect();
+
+        assert_eq!(Mt19937::recover_seed(12_345, &outputs), Some(3_141_592_653));
+        assert_eq!(Mt19937::recover_seed(12_344, &outputs), None);
+
+        let target = Target::for_max(Mapping::Identity, outputs, u32::MAX).unwrap();
+        assert_eq!(Mt19937::crack(12_345, &target), [3_141_592_653]);
+    }
+}
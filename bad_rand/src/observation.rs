@@ -0,0 +1,216 @@
+//! What the target program did with `rand()` before we got to see it.
+
+use crate::glibc::RAND_MAX;
+use std::fmt;
+
+pub const DEFAULT_ALPHABET: &[u8] =
+    b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mapping {
+    /// `rand()` itself
+    Identity,
+    /// `rand() % n`
+    Modulo(u32),
+    /// `rand() / (RAND_MAX / n)`
+    Scale(u32),
+    /// top `k` bits, `rand() >> (31 - k)`
+    HighBits(u32),
+}
+
+impl Mapping {
+    pub fn apply(self, output: u32) -> u32 {
+        self.apply_to(output, RAND_MAX)
+    }
+
+    pub fn is_valid(self) -> bool {
+        self.is_valid_for(RAND_MAX)
+    }
+
+    /// Number of distinct values the mapping can produce.
+    pub fn range(self) -> u64 {
+        self.range_for(RAND_MAX)
+    }
+
+    /// Same as `apply` for a generator whose outputs go up to `max` rather than `RAND_MAX`.
+    pub fn apply_to(self, output: u32, max: u32) -> u32 {
+        match self {
+            Mapping::Identity => output,
+            Mapping::Modulo(n) => output % n,
+            Mapping::Scale(n) => output / (max / n),
+            Mapping::HighBits(k) => output >> (bits(max) - k),
+        }
+    }
+
+    pub fn is_valid_for(self, max: u32) -> bool {
+        match self {
+            Mapping::Identity => true,
+            Mapping::Modulo(n) => n != 0,
+            Mapping::Scale(n) => n != 0 && n <= max,
+            Mapping::HighBits(k) => k != 0 && k <= bits(max),
+        }
+    }
+
+    pub fn range_for(self, max: u32) -> u64 {
+        match self {
+            Mapping::Identity => max as u64 + 1,
+            Mapping::Modulo(n) => n as u64,
+            // max isn't divisible by n, the remainder gets a value of its own
+            Mapping::Scale(n) => (max / (max / n)) as u64 + 1,
+            Mapping::HighBits(k) => 1 << k,
+        }
+    }
+}
+
+// generators return all values of `bits(max)` bits
+fn bits(max: u32) -> u32 {
+    32 - max.leading_zeros()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TargetError {
+    Empty,
+    InvalidMapping(Mapping),
+    NotInAlphabet(char),
+    OutOfRange(u32),
+    InvalidPattern(String),
+}
+
+impl fmt::Display for TargetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TargetError::Empty => write!(f, "target is empty"),
+            TargetError::InvalidMapping(mapping) => write!(f, "invalid mapping {:?}", mapping),
+            TargetError::NotInAlphabet(c) => write!(f, "{:?} is not in the alphabet", c),
+            TargetError::OutOfRange(x) => write!(f, "{} can't be produced by the mapping", x),
+            TargetError::InvalidPattern(e) => write!(f, "invalid pattern: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for TargetError {}
+
+/// Consecutive observed outputs after the mapping.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Target {
+    mapping: Mapping,
+    values:  Vec<u32>,
+    // largest output of the generator
+    max:     u32,
+}
+
+impl Target {
+    pub fn new(mapping: Mapping, values: Vec<u32>) -> Result<Self, TargetError> {
+        Self::for_max(mapping, values, RAND_MAX)
+    }
+
+    /// Observations of a generator whose outputs go up to `max` rather than `RAND_MAX`.
+    pub fn for_max(mapping: Mapping, values: Vec<u32>, max: u32) -> Result<Self, TargetError> {
+        Self { mapping, values, max }.validate()
+    }
+
+    /// The same observations of a generator whose outputs go up to `max`.
+    pub fn with_max(self, max: u32) -> Result<Self, TargetError> {
+        Self { max, ..self }.validate()
+    }
+
+    fn validate(self) -> Result<Self, TargetError> {
+        if self.values.is_empty() {
+            return Err(TargetError::Empty);
+        }
+        if !self.mapping.is_valid_for(self.max) {
+            return Err(TargetError::InvalidMapping(self.mapping));
+        }
+        let range = self.mapping.range_for(self.max);
+        if let Some(&x) = self.values.iter().find(|&&x| x as u64 >= range) {
+            return Err(TargetError::OutOfRange(x));
+        }
+
+        Ok(self)
+    }
+
+    /// String produced by `alphabet[rand() % alphabet.len()]`.
+    pub fn from_text(text: &[u8], alphabet: &[u8]) -> Result<Self, TargetError> {
+        let values = text
+            .iter()
+            .map(|&c| {
+                alphabet
+                    .iter()
+                    .position(|&x| x == c)
+                    .map(|i| i as u32)
+                    .ok_or(TargetError::NotInAlphabet(c as char))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::new(Mapping::Modulo(alphabet.len() as u32), values)
+    }
+
+    pub fn mapping(&self) -> Mapping {
+        self.mapping
+    }
+
+    pub fn max(&self) -> u32 {
+        self.max
+    }
+
+    pub fn values(&self) -> &[u32] {
+        &self.values
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Whether `outputs` of the generator produce the target, stops at the first mismatch.
+    pub fn matches(&self, outputs: impl Iterator<Item = u32>) -> bool {
+        outputs.zip(self.values.iter()).all(|(x, &y)| self.mapping.apply_to(x, self.max) == y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{forward::ForwardMatrix, glibc::GlibcRandom};
+
+    #[test]
+    fn text_target() {
+        let mut rng = GlibcRandom::new(1337);
+        (0..100).for_each(|_| {
+            rng.rand();
+        });
+        let text: Vec<u8> = (0..10)
+            .map(|_| DEFAULT_ALPHABET[rng.rand() as usize % DEFAULT_ALPHABET.len()])
+            .collect();
+
+        let target = Target::from_text(&text, DEFAULT_ALPHABET).unwrap();
+        let forward = ForwardMatrix::new(Default::default(), 100, target.len());
+
+        assert!(target.matches(forward.outputs(1337)));
+        assert!(!target.matches(forward.outputs(1338)));
+    }
+
+    #[test]
+    fn ranges() {
+        // monotonic mappings reach their largest value at RAND_MAX
+        for &mapping in [Mapping::Scale(62), Mapping::Scale(1 << 20), Mapping::HighBits(5)].iter() {
+            assert_eq!(mapping.apply(RAND_MAX) as u64 + 1, mapping.range(), "{:?}", mapping);
+        }
+    }
+
+    #[test]
+    fn invalid_targets() {
+        assert_eq!(
+            Target::from_text(b"ab!", DEFAULT_ALPHABET),
+            Err(TargetError::NotInAlphabet('!'))
+        );
+        assert_eq!(Target::new(Mapping::Modulo(10), vec![]), Err(TargetError::Empty));
+        for &mapping in [Mapping::Modulo(0), Mapping::Scale(0), Mapping::HighBits(32)].iter() {
+            assert_eq!(Target::new(mapping, vec![0]), Err(TargetError::InvalidMapping(mapping)));
+        }
+        assert_eq!(Target::new(Mapping::Modulo(10), vec![3, 10]), Err(TargetError::OutOfRange(10)));
+    }
+}
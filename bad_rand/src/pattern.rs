@@ -0,0 +1,327 @@
+//! Targets that aren't fully known: unreadable positions, positions narrowed down to a few
+//! characters, and a number of positions that may be read wrong altogether.
+//!
+//! Without mismatches allowed, the longest run of exactly known positions goes through the
+//! solver like any other target and the rest of the pattern is checked on the seeds it lets
+//! through. With mismatches there's no telling which positions to trust, so every seed gets its
+//! mismatches counted, `LANES` seeds at a time, until too many of them are wrong.
+
+use std::ops::Range;
+
+use crate::{
+    batch::{self, Check, SeedStates, LANES},
+    forward::ForwardMatrix,
+    glibc::StateType,
+    observation::{Mapping, Target, TargetError},
+    solver::{self, Pruned, Solution},
+};
+
+/// Values an observation may have, `None` for any.
+pub type Position = Option<Vec<u32>>;
+
+/// Consecutive observations with wildcards and sets of allowed values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    mapping:   Mapping,
+    positions: Vec<Position>,
+}
+
+impl Pattern {
+    pub fn new(mapping: Mapping, mut positions: Vec<Position>) -> Result<Self, TargetError> {
+        if !mapping.is_valid() {
+            return Err(TargetError::InvalidMapping(mapping));
+        }
+        for values in positions.iter_mut().flatten() {
+            values.sort_unstable();
+            values.dedup();
+            match values.iter().find(|&&x| x as u64 >= mapping.range()) {
+                Some(&x) => return Err(TargetError::OutOfRange(x)),
+                None if values.is_empty() => {
+                    return Err(TargetError::InvalidPattern("empty set of values".into()))
+                }
+                None => {}
+            }
+        }
+        if positions.iter().all(Option::is_none) {
+            return Err(TargetError::Empty);
+        }
+
+        Ok(Self { mapping, positions })
+    }
+
+    /// String produced by `alphabet[rand() % alphabet.len()]`, with `?` for an unknown
+    /// character and `[...]` for one of a few, such as `[0O]` or `[a-f]`.
+    pub fn from_text(text: &[u8], alphabet: &[u8]) -> Result<Self, TargetError> {
+        let index = |c: u8| {
+            alphabet
+                .iter()
+                .position(|&x| x == c)
+                .map(|i| i as u32)
+                .ok_or(TargetError::NotInAlphabet(c as char))
+        };
+
+        let mut positions = vec![];
+        let mut rest = text;
+        while let Some((&c, tail)) = rest.split_first() {
+            rest = tail;
+            match c {
+                b'?' => positions.push(None),
+                b'[' => {
+                    let end = rest.iter().position(|&x| x == b']').ok_or_else(|| {
+                        TargetError::InvalidPattern("[ without a matching ]".into())
+                    })?;
+                    let (set, tail) = rest.split_at(end);
+                    rest = &tail[1..];
+
+                    let mut values = vec![];
+                    let mut i = 0;
+                    while i < set.len() {
+                        if i + 2 < set.len() && set[i + 1] == b'-' {
+                            for c in set[i]..=set[i + 2] {
+                                values.push(index(c)?);
+                            }
+                            i += 3;
+                        } else {
+                            values.push(index(set[i])?);
+                            i += 1;
+                        }
+                    }
+                    positions.push(Some(values));
+                }
+                c => positions.push(Some(vec![index(c)?])),
+            }
+        }
+
+        Self::new(Mapping::Modulo(alphabet.len() as u32), positions)
+    }
+
+    pub fn mapping(&self) -> Mapping {
+        self.mapping
+    }
+
+    pub fn positions(&self) -> &[Position] {
+        &self.positions
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Whether the observation at position `i` may come from `output`.
+    pub fn admits(&self, i: usize, output: u32) -> bool {
+        match &self.positions[i] {
+            None => true,
+            Some(values) => values.binary_search(&self.mapping.apply(output)).is_ok(),
+        }
+    }
+
+    /// Positions `outputs` don't fit.
+    pub fn mismatches(&self, outputs: impl Iterator<Item = u32>) -> usize {
+        outputs.enumerate().take(self.len()).filter(|&(i, x)| !self.admits(i, x)).count()
+    }
+
+    /// Longest run of positions known exactly, as a target.
+    fn exact_run(&self) -> Option<(Range<usize>, Target)> {
+        let exact = |i: usize| matches!(&self.positions[i], Some(values) if values.len() == 1);
+
+        let mut best = 0..0;
+        let mut start = 0;
+        for i in 0..=self.len() {
+            if i == self.len() || !exact(i) {
+                if i - start > best.len() {
+                    best = start..i;
+                }
+                start = i + 1;
+            }
+        }
+        if best.is_empty() {
+            return None;
+        }
+
+        let values = self.positions[best.clone()].iter().flatten().map(|x| x[0]).collect();
+        Target::new(self.mapping, values).ok().map(|target| (best, target))
+    }
+}
+
+/// What the solver made of the exact run.
+#[derive(Debug, Clone)]
+enum Prefilter {
+    None,
+    Seeds(Vec<u32>),
+    Pruned(Pruned),
+}
+
+/// Seeds producing a pattern with at most `tolerance` mismatches.
+#[derive(Debug, Clone)]
+pub struct PatternCheck {
+    pattern:   Pattern,
+    tolerance: usize,
+    forward:   ForwardMatrix,
+    // positions that can mismatch, most selective first
+    order:     Vec<usize>,
+    prefilter: Prefilter,
+}
+
+impl PatternCheck {
+    pub fn new(ty: StateType, skip: u64, pattern: Pattern, tolerance: usize) -> Self {
+        let prefilter = match pattern.exact_run() {
+            Some((run, target)) if tolerance == 0 => {
+                match solver::solve(ty, skip + run.start as u64, &target) {
+                    Solution::Impossible(_) => Prefilter::Seeds(vec![]),
+                    Solution::Seeds(seeds) => Prefilter::Seeds(seeds),
+                    Solution::Search(pruned) => Prefilter::Pruned(pruned),
+                }
+            }
+            _ => Prefilter::None,
+        };
+
+        let range = pattern.mapping.range() as usize;
+        let mut order: Vec<usize> =
+            (0..pattern.len()).filter(|&i| pattern.positions[i].is_some()).collect();
+        order.sort_by_key(|&i| pattern.positions[i].as_ref().map_or(range, Vec::len));
+
+        let forward = ForwardMatrix::new(ty, skip, pattern.len());
+        Self { pattern, tolerance, forward, order, prefilter }
+    }
+
+    pub fn pattern(&self) -> &Pattern {
+        &self.pattern
+    }
+
+    /// Positions the outputs of `seed` don't fit.
+    pub fn mismatches(&self, seed: u32) -> usize {
+        self.pattern.mismatches(self.forward.outputs(seed))
+    }
+}
+
+impl Check for PatternCheck {
+    fn state_type(&self) -> StateType {
+        self.forward.state_type()
+    }
+
+    fn lanes(&self, states: &SeedStates) -> u32 {
+        let all = u32::MAX >> (32 - LANES);
+        match &self.prefilter {
+            Prefilter::Pruned(pruned) => return pruned.lanes(states),
+            Prefilter::Seeds(seeds) => {
+                let base = states.base();
+                let lanes = seeds.iter().filter_map(|&x| (x as u64).checked_sub(base));
+                return lanes.filter(|&i| i < LANES as u64).fold(0, |acc, i| acc | 1 << i);
+            }
+            Prefilter::None => {}
+        }
+
+        let ty = self.forward.state_type();
+        let mut mask = all;
+        let mut mismatches = [0; LANES];
+        for &i in self.order.iter() {
+            let raw = batch::dot(&self.forward.rows()[i], states.words());
+            for (lane, &x) in raw.iter().enumerate() {
+                if !self.pattern.admits(i, ty.output(x)) {
+                    mismatches[lane] += 1;
+                    if mismatches[lane] > self.tolerance {
+                        mask &= !(1 << lane);
+                    }
+                }
+            }
+            if mask == 0 {
+                break;
+            }
+        }
+
+        mask
+    }
+
+    fn matches(&self, seed: u32) -> bool {
+        let passed = match &self.prefilter {
+            Prefilter::None => true,
+            Prefilter::Seeds(seeds) => seeds.contains(&seed),
+            Prefilter::Pruned(pruned) => pruned.matches(seed),
+        };
+        passed && self.mismatches(seed) <= self.tolerance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{batch::Batched, glibc::GlibcRandom, observation::DEFAULT_ALPHABET};
+    use std::sync::Arc;
+
+    fn text(seed: u32, len: usize) -> Vec<u8> {
+        let mut rng = GlibcRandom::new(seed);
+        (0..len).map(|_| DEFAULT_ALPHABET[rng.rand() as usize % DEFAULT_ALPHABET.len()]).collect()
+    }
+
+    #[test]
+    fn parses() {
+        let pattern = Pattern::from_text(b"a?[0O][a-c]", DEFAULT_ALPHABET).unwrap();
+        assert_eq!(
+            pattern.positions(),
+            [Some(vec![0]), None, Some(vec![40, 52]), Some(vec![0, 1, 2])]
+        );
+
+        assert!(matches!(
+            Pattern::from_text(b"ab[cd", DEFAULT_ALPHABET),
+            Err(TargetError::InvalidPattern(_))
+        ));
+        assert!(matches!(
+            Pattern::from_text(b"ab[]", DEFAULT_ALPHABET),
+            Err(TargetError::InvalidPattern(_))
+        ));
+        assert_eq!(Pattern::from_text(b"??", DEFAULT_ALPHABET), Err(TargetError::Empty));
+        assert_eq!(
+            Pattern::from_text(b"a[b!]", DEFAULT_ALPHABET),
+            Err(TargetError::NotInAlphabet('!'))
+        );
+    }
+
+    #[test]
+    fn wildcards_and_sets() {
+        let seed = 1_000_077;
+        let mut smudged = text(seed, 12);
+        smudged[3] = b'?';
+        smudged[7] = b'?';
+        let mut pattern = smudged[..9].to_vec();
+        pattern.extend_from_slice(b"[");
+        pattern.push(smudged[9]);
+        pattern.extend_from_slice(b"xyz]");
+        pattern.extend_from_slice(&smudged[10..]);
+
+        let pattern = Pattern::from_text(&pattern, DEFAULT_ALPHABET).unwrap();
+        let check = Arc::new(PatternCheck::new(StateType::default(), 0, pattern, 0));
+        assert!(matches!(check.prefilter, Prefilter::Pruned(_)));
+
+        let found: Vec<u64> = Batched::new(check, 1_000_000..1_000_200).collect();
+        assert_eq!(found, [seed as u64]);
+    }
+
+    #[test]
+    fn counts_mismatches() {
+        let seed = 1_000_033;
+        let mut wrong = text(seed, 14);
+        wrong[2] = b'?';
+        wrong[9] = b'?';
+        // on top of the unreadable ones
+        for &i in [4, 11].iter() {
+            wrong[i] = if wrong[i] == b'a' { b'b' } else { b'a' };
+        }
+
+        let pattern = Pattern::from_text(&wrong, DEFAULT_ALPHABET).unwrap();
+        let seeds = 1_000_000..1_000_100;
+        for tolerance in 0..3 {
+            let check =
+                Arc::new(PatternCheck::new(StateType::default(), 0, pattern.clone(), tolerance));
+            assert_eq!(check.mismatches(seed), 2);
+
+            let expected: Vec<u64> =
+                seeds.clone().filter(|&x| check.mismatches(x as u32) <= tolerance).collect();
+            assert_eq!(expected.contains(&(seed as u64)), tolerance == 2);
+            assert_eq!(Batched::new(check, seeds.clone()).collect::<Vec<_>>(), expected);
+        }
+    }
+}
@@ -0,0 +1,285 @@
+//! Recovering the state from full `random()` outputs, no seed needed.
+//!
+//! Consecutive outputs of the additive generators satisfy `o[i] = o[i - deg] + o[i - sep] + c`
+//! (mod 2^31), where the carry `c` is the AND of the dropped low bits of both terms. The low bits
+//! themselves follow `l[i] = l[i - deg] ^ l[i - sep]`, so all of them are linear over GF(2) in the
+//! low bits of the first `deg` outputs. Every carry of 1 tells that two low bits are set, and every
+//! carry of 0 that at least one of two isn't, which is enough to pin the unknowns down.
+
+use crate::glibc::{GlibcRandom, StateType, MAX_DEGREE, RAND_MAX};
+use std::fmt;
+
+/// Observation that can't come from the generator, given everything seen before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Contradiction {
+    pub index: usize,
+    pub value: u32,
+}
+
+impl fmt::Display for Contradiction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "output #{} ({}) contradicts the previous ones", self.index, self.value)
+    }
+}
+
+impl std::error::Error for Contradiction {}
+
+/// GF(2) linear system over at most 64 unknowns, kept in reduced row echelon form.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct System {
+    // (equation, right hand side), every pivot (lowest set bit) occurs in its row only
+    rows: Vec<(u64, bool)>,
+}
+
+impl System {
+    pub(crate) fn reduce(&self, mut mask: u64, mut value: bool) -> (u64, bool) {
+        for &(row, rhs) in self.rows.iter() {
+            if mask & row & row.wrapping_neg() != 0 {
+                mask ^= row;
+                value ^= rhs;
+            }
+        }
+        (mask, value)
+    }
+
+    /// Value of the expression if the system determines it.
+    pub(crate) fn eval(&self, mask: u64) -> Option<bool> {
+        match self.reduce(mask, false) {
+            (0, value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// `false` if the equation contradicts the system.
+    pub(crate) fn add(&mut self, mask: u64, value: bool) -> bool {
+        let (mask, value) = self.reduce(mask, value);
+        if mask == 0 {
+            return !value;
+        }
+
+        let pivot = mask & mask.wrapping_neg();
+        for row in self.rows.iter_mut().filter(|(row, _)| row & pivot != 0) {
+            row.0 ^= mask;
+            row.1 ^= value;
+        }
+        self.rows.push((mask, value));
+
+        true
+    }
+
+    pub(crate) fn equations(&self) -> &[(u64, bool)] {
+        &self.rows
+    }
+
+    pub(crate) fn rank(&self) -> usize {
+        self.rows.len()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Predictor {
+    ty:      StateType,
+    outputs: Vec<u32>,
+    // low bit of each output as a combination of the low bits of the first `deg` outputs
+    lows:    Vec<u64>,
+    system:  System,
+    // pairs of outputs whose low bits aren't both set
+    pending: Vec<(usize, usize)>,
+}
+
+impl Predictor {
+    pub fn new(ty: StateType) -> Self {
+        Self { ty, outputs: vec![], lows: vec![], system: System::default(), pending: vec![] }
+    }
+
+    pub fn outputs(&self) -> &[u32] {
+        &self.outputs
+    }
+
+    /// Whether the state is fully known.
+    pub fn is_resolved(&self) -> bool {
+        if self.ty.is_lcg() {
+            !self.outputs.is_empty()
+        } else {
+            self.outputs.len() >= self.ty.degree() && self.system.rank() == self.ty.degree()
+        }
+    }
+
+    /// Adds the next output, rejecting it if it contradicts the model.
+    pub fn push(&mut self, value: u32) -> Result<(), Contradiction> {
+        let contradiction = Contradiction { index: self.outputs.len(), value };
+        if value > RAND_MAX {
+            return Err(contradiction);
+        }
+
+        if self.ty.is_lcg() {
+            // output is the whole state
+            if let Some(expected) = self.state().map(|mut rng| rng.random()) {
+                if expected != value {
+                    return Err(contradiction);
+                }
+            }
+            self.outputs.push(value);
+            return Ok(());
+        }
+
+        if self.observe(value) {
+            Ok(())
+        } else {
+            Err(contradiction)
+        }
+    }
+
+    // nothing changes unless the output fits: the system has at most 64 rows, so it's worked on
+    // as a copy, and the pending pairs are only dropped once everything checks out
+    fn observe(&mut self, value: u32) -> bool {
+        let (degree, separation) = (self.ty.degree(), self.ty.separation());
+        let i = self.outputs.len();
+        if i < degree {
+            self.outputs.push(value);
+            self.lows.push(1 << i);
+            return true;
+        }
+
+        let (far, near) = (i - degree, i - separation);
+        let sum = self.outputs[far].wrapping_add(self.outputs[near]);
+        let mut system = self.system.clone();
+        let pair = match value.wrapping_sub(sum) & RAND_MAX {
+            0 => Some((far, near)),
+            1 if system.add(self.lows[far], true) && system.add(self.lows[near], true) => None,
+            _ => return false,
+        };
+
+        self.pending.extend(pair);
+        let settled = match self.propagate(&mut system) {
+            Some(settled) => settled,
+            None => {
+                self.pending.truncate(self.pending.len() - pair.iter().count());
+                return false;
+            }
+        };
+
+        let mut k = 0;
+        self.pending.retain(|_| {
+            k += 1;
+            !settled[k - 1]
+        });
+        self.system = system;
+        self.outputs.push(value);
+        self.lows.push(self.lows[far] ^ self.lows[near]);
+        true
+    }
+
+    // turns "not both set" into equations of `system` once one side is known, returning which
+    // pending pairs that settles, `None` on a contradiction
+    fn propagate(&self, system: &mut System) -> Option<Vec<bool>> {
+        let mut settled = vec![false; self.pending.len()];
+        loop {
+            let mut changed = false;
+            for (k, &(a, b)) in self.pending.iter().enumerate() {
+                if settled[k] {
+                    continue;
+                }
+                let (a, b) = (self.lows[a], self.lows[b]);
+
+                let implied = match (system.eval(a), system.eval(b)) {
+                    (Some(true), Some(true)) => return None,
+                    (Some(false), _) | (_, Some(false)) => None,
+                    (Some(true), None) => Some(b),
+                    (None, Some(true)) => Some(a),
+                    (None, None) => continue,
+                };
+
+                if let Some(mask) = implied {
+                    if !system.add(mask, false) {
+                        return None;
+                    }
+                    changed = true;
+                }
+                settled[k] = true;
+            }
+
+            if !changed {
+                return Some(settled);
+            }
+        }
+    }
+
+    /// Generator positioned right after the last observed output.
+    pub fn state(&self) -> Option<GlibcRandom> {
+        if !self.is_resolved() {
+            return None;
+        }
+
+        let mut state = [0; MAX_DEGREE];
+        let n = self.outputs.len();
+
+        if self.ty.is_lcg() {
+            state[0] = self.outputs[n - 1];
+        } else {
+            let (degree, separation) = (self.ty.degree(), self.ty.separation());
+
+            // output t calls ago sits at separation - 1 - t, counting from the rear pointer
+            for t in 0..degree {
+                let low = self.system.eval(self.lows[n - 1 - t])? as u32;
+                let index = (separation + degree - 1 - t) % degree;
+                state[index] = self.outputs[n - 1 - t] << 1 | low;
+            }
+        }
+
+        Some(GlibcRandom::from_state(self.ty, state))
+    }
+
+    /// Next `count` outputs, once the state is known.
+    pub fn predict(&self, count: usize) -> Option<Vec<u32>> {
+        let mut rng = self.state()?;
+        Some((0..count).map(|_| rng.random()).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recovers_state() {
+        for &ty in StateType::ALL.iter() {
+            for seed in 1..20 {
+                let mut rng = GlibcRandom::with_type(ty, seed);
+                let mut predictor = Predictor::new(ty);
+
+                while !predictor.is_resolved() {
+                    assert!(predictor.outputs().len() < 10_000, "{:?}, seed {}", ty, seed);
+                    predictor.push(rng.random()).unwrap();
+                }
+
+                let expected: Vec<u32> = (0..100).map(|_| rng.random()).collect();
+                assert_eq!(predictor.predict(100), Some(expected), "{:?}, seed {}", ty, seed);
+            }
+        }
+    }
+
+    #[test]
+    fn flags_contradictions() {
+        let mut rng = GlibcRandom::new(42);
+        let mut predictor = Predictor::new(StateType::Type3);
+
+        (0..31).for_each(|_| predictor.push(rng.random()).unwrap());
+        let next = rng.random();
+        assert_eq!(
+            predictor.push(next ^ 0x100),
+            Err(Contradiction { index: 31, value: next ^ 0x100 })
+        );
+        assert_eq!(predictor.push(next), Ok(()));
+
+        while !predictor.is_resolved() {
+            predictor.push(rng.random()).unwrap();
+        }
+
+        // being off by the carry is only caught once the low bits are known
+        let next = rng.random();
+        assert!(predictor.push(next.wrapping_add(1) & RAND_MAX).is_err());
+        assert!(predictor.push(next.wrapping_sub(1) & RAND_MAX).is_err());
+        assert_eq!(predictor.push(next), Ok(()));
+    }
+}
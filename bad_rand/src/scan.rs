@@ -0,0 +1,424 @@
+//! Brute force over the seed space in chunks, so that it can be split between processes and
+//! resumed after being killed.
+//!
+//! Chunks are handed to shards round-robin, so every shard gets a similar mix of small and large
+//! seeds. Each finished chunk is appended to the checkpoint file together with its matches as a
+//! single line, a line cut short by a kill is just ignored on resume.
+
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    ops::RangeInclusive,
+    path::Path,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::{self, Receiver},
+        Arc, Mutex,
+    },
+};
+
+use rayon::prelude::*;
+
+/// Seeds per chunk by default.
+pub const CHUNK_SIZE: u64 = 1 << 24;
+
+const HEADER: &str = "bad_rand checkpoint";
+
+/// The `index`-th of `count` processes splitting the seeds between them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shard {
+    pub index: u64,
+    pub count: u64,
+}
+
+impl Default for Shard {
+    fn default() -> Self {
+        Self { index: 0, count: 1 }
+    }
+}
+
+impl std::fmt::Display for Shard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.index, self.count)
+    }
+}
+
+impl FromStr for Shard {
+    type Err = String;
+
+    /// `i/N`, `i` counting from 0.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid shard {:?}, expected i/N with i < N", s);
+        let (index, count) = s.split_once('/').ok_or_else(invalid)?;
+        let shard = Self {
+            index: index.trim().parse().map_err(|_| invalid())?,
+            count: count.trim().parse().map_err(|_| invalid())?,
+        };
+        if shard.index >= shard.count {
+            return Err(invalid());
+        }
+        Ok(shard)
+    }
+}
+
+/// Finished chunks of a search, kept in a file.
+#[derive(Debug)]
+pub struct Checkpoint {
+    file: Mutex<File>,
+    // matches of every finished chunk
+    done: HashMap<u64, Vec<(u64, u64)>>,
+}
+
+impl Checkpoint {
+    /// Opens the file, creating it if needed. `search` describes what is searched for, resuming
+    /// a different search from the same file is an error.
+    pub fn open(path: &Path, search: &str) -> io::Result<Self> {
+        let header = format!("{} {}", HEADER, search);
+        let mut done = HashMap::new();
+
+        let text = if path.exists() { std::fs::read_to_string(path)? } else { String::new() };
+        // a line without the newline was cut short
+        let mut lines = text.split_inclusive('\n').filter_map(|line| line.strip_suffix('\n'));
+        match lines.next() {
+            Some(line) if line == header => {}
+            None if text.is_empty() => {}
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{} belongs to another search", path.display()),
+                ))
+            }
+        }
+        for line in lines {
+            if let Some((chunk, matches)) = parse_line(line) {
+                done.insert(chunk, matches);
+            }
+        }
+
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        if text.is_empty() {
+            writeln!(file, "{}", header)?;
+        } else if !text.ends_with('\n') {
+            writeln!(file)?;
+        }
+
+        Ok(Self { file: Mutex::new(file), done })
+    }
+
+    pub fn is_done(&self, chunk: u64) -> bool {
+        self.done.contains_key(&chunk)
+    }
+
+    /// Number of chunks finished before this run.
+    pub fn done(&self) -> usize {
+        self.done.len()
+    }
+
+    fn record(&self, chunk: u64, matches: &[(u64, u64)]) -> io::Result<()> {
+        let mut line = chunk.to_string();
+        for (seed, skip) in matches.iter() {
+            line += &format!(" {}:{}", seed, skip);
+        }
+        line.push('\n');
+
+        self.file.lock().unwrap().write_all(line.as_bytes())
+    }
+}
+
+// `chunk seed:skip seed:skip ...`
+fn parse_line(line: &str) -> Option<(u64, Vec<(u64, u64)>)> {
+    let mut words = line.split(' ');
+    let chunk = words.next()?.parse().ok()?;
+    let matches = words
+        .map(|word| {
+            let (seed, skip) = word.split_once(':')?;
+            Some((seed.parse().ok()?, skip.parse().ok()?))
+        })
+        .collect::<Option<_>>()?;
+    Some((chunk, matches))
+}
+
+/// Seeds of a chunk, running out early once the scan is cancelled.
+#[derive(Debug, Clone)]
+pub struct Seeds {
+    range: RangeInclusive<u64>,
+    stop:  Arc<AtomicBool>,
+}
+
+impl Iterator for Seeds {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if self.stop.load(Ordering::Relaxed) {
+            return None;
+        }
+        self.range.next()
+    }
+}
+
+/// Seeds split into chunks, the ones of a single shard.
+#[derive(Debug, Clone)]
+pub struct Scan {
+    seeds:       RangeInclusive<u64>,
+    shard:       Shard,
+    chunk_size:  u64,
+    max_matches: Option<usize>,
+}
+
+impl Scan {
+    pub fn new(seeds: RangeInclusive<u64>, shard: Shard) -> Self {
+        Self { seeds, shard, chunk_size: CHUNK_SIZE, max_matches: None }
+    }
+
+    /// Only the seeds in `seeds`, chunks stay where they were.
+    pub fn with_seeds(self, seeds: RangeInclusive<u64>) -> Self {
+        Self { seeds, ..self }
+    }
+
+    /// Checkpoints made with another chunk size don't apply.
+    pub fn with_chunk_size(self, chunk_size: u64) -> Self {
+        assert_ne!(chunk_size, 0);
+        Self { chunk_size, ..self }
+    }
+
+    pub fn with_shard(self, shard: Shard) -> Self {
+        Self { shard, ..self }
+    }
+
+    /// Stops every worker once `max` matches are found. Which ones are found first depends on
+    /// the scheduling, so they aren't necessarily the smallest.
+    pub fn with_max_matches(self, max: usize) -> Self {
+        Self { max_matches: Some(max), ..self }
+    }
+
+    /// Same as `with_max_matches(1)`.
+    pub fn first(self) -> Self {
+        self.with_max_matches(1)
+    }
+
+    pub fn max_matches(&self) -> Option<usize> {
+        self.max_matches
+    }
+
+    /// Whether the seed is one of this shard's.
+    pub fn contains(&self, seed: u64) -> bool {
+        self.seeds.contains(&seed)
+            && (seed / self.chunk_size) % self.shard.count == self.shard.index
+    }
+
+    /// Chunks of this shard with their indices, chunk `i` holds seeds `i * chunk_size..`.
+    pub fn chunks(&self) -> Vec<(u64, RangeInclusive<u64>)> {
+        let size = self.chunk_size;
+        let (first, last) = (self.seeds.start() / size, self.seeds.end() / size);
+
+        (first..=last)
+            .filter(|chunk| chunk % self.shard.count == self.shard.index)
+            .map(|chunk| {
+                let start = (chunk * size).max(*self.seeds.start());
+                let end = (chunk * size + size - 1).min(*self.seeds.end());
+                (chunk, start..=end)
+            })
+            .collect()
+    }
+
+    /// Runs `search` over every chunk not finished yet and returns the `(seed, skip)` matches of
+    /// the whole shard, sorted. `progress` is called with the number of finished chunks.
+    pub fn run<F, I>(
+        &self,
+        checkpoint: Option<&Checkpoint>,
+        progress: impl Fn(usize, usize) + Sync,
+        search: F,
+    ) -> io::Result<Vec<(u64, u64)>>
+    where
+        F: Fn(Seeds) -> I + Sync,
+        I: Iterator<Item = (u64, u64)>,
+    {
+        let mut matches = self.drive(checkpoint, progress, search, |_| true)?;
+        matches.sort_unstable();
+        if let Some(max) = self.max_matches {
+            matches.truncate(max);
+        }
+        Ok(matches)
+    }
+
+    /// Matches in the order they are found, from a background thread. Dropping the receiver
+    /// cancels the scan.
+    pub fn stream<F, I>(self, search: F) -> Receiver<(u64, u64)>
+    where
+        F: Fn(Seeds) -> I + Send + Sync + 'static,
+        I: Iterator<Item = (u64, u64)>,
+    {
+        let (sender, receiver) = mpsc::channel();
+        let sender = Mutex::new(sender);
+
+        std::thread::spawn(move || {
+            let sink = |x| sender.lock().unwrap().send(x).is_ok();
+            // without a checkpoint there is nothing to fail
+            let _ = self.drive(None, |_, _| {}, search, sink);
+        });
+
+        receiver
+    }
+
+    // passes every match to `sink` as soon as it is found, `false` from it cancels the scan
+    fn drive<F, I>(
+        &self,
+        checkpoint: Option<&Checkpoint>,
+        progress: impl Fn(usize, usize) + Sync,
+        search: F,
+        sink: impl Fn((u64, u64)) -> bool + Sync,
+    ) -> io::Result<Vec<(u64, u64)>>
+    where
+        F: Fn(Seeds) -> I + Sync,
+        I: Iterator<Item = (u64, u64)>,
+    {
+        let chunks = self.chunks();
+        let total = chunks.len();
+        let finished = Mutex::new(0);
+        let stop = Arc::new(AtomicBool::new(false));
+        let found = AtomicUsize::new(0);
+
+        // counts a match, true once there are enough of them
+        let enough = |x| {
+            let count = found.fetch_add(1, Ordering::Relaxed) + 1;
+            !sink(x) || self.max_matches.is_some_and(|max| count >= max)
+        };
+
+        let mut matches = vec![];
+        let todo: Vec<_> = match checkpoint {
+            Some(checkpoint) => {
+                for (chunk, _) in chunks.iter() {
+                    if let Some(done) = checkpoint.done.get(chunk) {
+                        for &x in done.iter() {
+                            matches.push(x);
+                            if enough(x) {
+                                stop.store(true, Ordering::Relaxed);
+                            }
+                        }
+                        *finished.lock().unwrap() += 1;
+                    }
+                }
+                chunks.into_iter().filter(|(chunk, _)| !checkpoint.is_done(*chunk)).collect()
+            }
+            None => chunks,
+        };
+
+        let found: Vec<Vec<(u64, u64)>> = todo
+            .into_par_iter()
+            .map(|(chunk, range)| {
+                let mut found = vec![];
+                if stop.load(Ordering::Relaxed) {
+                    return Ok(found);
+                }
+
+                for x in search(Seeds { range, stop: stop.clone() }) {
+                    found.push(x);
+                    if enough(x) {
+                        stop.store(true, Ordering::Relaxed);
+                    }
+                }
+
+                // a cancelled chunk may have missed some seeds
+                if stop.load(Ordering::Relaxed) {
+                    return Ok(found);
+                }
+                if let Some(checkpoint) = checkpoint {
+                    checkpoint.record(chunk, &found)?;
+                }
+
+                let mut finished = finished.lock().unwrap();
+                *finished += 1;
+                progress(*finished, total);
+                Ok(found)
+            })
+            .collect::<io::Result<_>>()?;
+
+        matches.extend(found.into_iter().flatten());
+        Ok(matches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: u64 = 1000;
+
+    #[test]
+    fn shards_cover_the_seeds_once() {
+        let seeds = 5..=5 * SIZE + 7;
+        let mut covered = vec![];
+        for index in 0..3 {
+            let scan = Scan::new(seeds.clone(), Shard { index, count: 3 }).with_chunk_size(SIZE);
+            covered.extend(scan.chunks().into_iter().map(|(_, seeds)| seeds));
+        }
+        covered.sort_by_key(|seeds| *seeds.start());
+
+        assert_eq!(covered.len(), 6);
+        assert_eq!(*covered[0].start(), 5);
+        assert_eq!(*covered[5].end(), 5 * SIZE + 7);
+        for pair in covered.windows(2) {
+            assert_eq!(pair[0].end() + 1, *pair[1].start());
+        }
+
+        assert_eq!("2/3".parse(), Ok(Shard { index: 2, count: 3 }));
+        assert!("3/3".parse::<Shard>().is_err());
+        assert!("1".parse::<Shard>().is_err());
+    }
+
+    // seeds divisible by 500 match, every chunk has two of them
+    fn search(seeds: Seeds) -> impl Iterator<Item = (u64, u64)> {
+        seeds.filter(|x| x % 500 == 0).map(|x| (x, 7))
+    }
+
+    #[test]
+    fn resumes_from_checkpoint() {
+        let path = std::env::temp_dir().join(format!("bad_rand-{}.checkpoint", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let scan = Scan::new(0..=3 * SIZE - 1, Shard::default()).with_chunk_size(SIZE);
+        let expected = scan.run(None, |_, _| {}, search).unwrap();
+        assert_eq!(expected.len(), 6);
+
+        // pretend the first run got killed after the middle chunk and a half-written line
+        let checkpoint = Checkpoint::open(&path, "test").unwrap();
+        checkpoint.record(1, &expected[2..4]).unwrap();
+        checkpoint.file.lock().unwrap().write_all(b"2").unwrap();
+        drop(checkpoint);
+
+        let checkpoint = Checkpoint::open(&path, "test").unwrap();
+        assert_eq!(checkpoint.done(), 1);
+        let searched = Mutex::new(vec![]);
+        let matches = scan
+            .run(
+                Some(&checkpoint),
+                |_, _| {},
+                |seeds| {
+                    searched.lock().unwrap().push(*seeds.range.start() / SIZE);
+                    search(seeds)
+                },
+            )
+            .unwrap();
+        assert_eq!(matches, expected);
+        assert!(!searched.lock().unwrap().contains(&1));
+
+        assert!(Checkpoint::open(&path, "another").is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn stops_early() {
+        let scan = Scan::new(0..=3 * SIZE - 1, Shard::default()).with_chunk_size(SIZE);
+
+        let all = scan.run(None, |_, _| {}, search).unwrap();
+        let some = scan.clone().with_max_matches(3).run(None, |_, _| {}, search).unwrap();
+        assert_eq!(some.len(), 3);
+        assert!(some.iter().all(|x| all.contains(x)));
+
+        let first = scan.clone().first().stream(search).recv().unwrap();
+        assert!(all.contains(&first));
+        assert_eq!(scan.stream(search).iter().count(), all.len());
+    }
+}
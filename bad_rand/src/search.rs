@@ -0,0 +1,405 @@
+//! Everything `crack` does for glibc as a library: the solver, then the brute force over whatever
+//! it leaves, with matches returned as a `Vec` or streamed as they are found.
+
+use std::{
+    io,
+    ops::{Range, RangeInclusive},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::Receiver,
+        Arc,
+    },
+};
+
+use rayon::prelude::*;
+
+use crate::{
+    batch::Batched,
+    candidates::{Candidate, SeedSource},
+    glibc::StateType,
+    kernel::{Blocks, KernelCheck},
+    observation::Target,
+    pattern::{Pattern, PatternCheck},
+    predict::Contradiction,
+    scan::{Checkpoint, Scan, Shard},
+    sliding::SlidingSearch,
+    solver::{self, Pruned, Solution},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Match {
+    pub seed: u32,
+    pub skip: u64,
+}
+
+impl From<(u64, u64)> for Match {
+    fn from((seed, skip): (u64, u64)) -> Self {
+        Self { seed: seed as u32, skip }
+    }
+}
+
+/// How seeds are checked against an exact target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backend {
+    /// The solver, then batches of seeds for whatever it leaves.
+    #[default]
+    Solver,
+    /// The port of `bad_rand_cuda`'s kernel, see [`crate::kernel`].
+    Kernel,
+}
+
+impl Backend {
+    pub const ALL: [Backend; 2] = [Backend::Solver, Backend::Kernel];
+}
+
+#[derive(Debug, Clone)]
+enum Matcher {
+    // the solver needed no brute force
+    Solved(Vec<u32>),
+    Pruned(Arc<Pruned>),
+    Sliding(Arc<SlidingSearch>),
+    Pattern(Arc<PatternCheck>),
+    Kernel(Arc<KernelCheck>),
+}
+
+impl Matcher {
+    fn matches<I>(&self, seeds: I, skip: u64) -> Box<dyn Iterator<Item = (u64, u64)>>
+    where
+        I: Iterator<Item = u64> + 'static,
+    {
+        match self {
+            Matcher::Solved(solved) => {
+                let solved = solved.clone();
+                Box::new(
+                    seeds.filter(move |&x| solved.contains(&(x as u32))).map(move |x| (x, skip)),
+                )
+            }
+            Matcher::Pruned(pruned) => {
+                Box::new(Batched::new(pruned.clone(), seeds).map(move |x| (x, skip)))
+            }
+            Matcher::Pattern(check) => {
+                Box::new(Batched::new(check.clone(), seeds).map(move |x| (x, skip)))
+            }
+            Matcher::Kernel(check) => Box::new(Blocks::new(check.clone(), seeds)),
+            Matcher::Sliding(search) => {
+                let search = search.clone();
+                let mut outputs = vec![];
+                Box::new(seeds.flat_map(move |seed| {
+                    let skips = search.skips(seed as u32, &mut outputs);
+                    skips.into_iter().map(move |skip| (seed, skip))
+                }))
+            }
+        }
+    }
+}
+
+/// Seeds of `srandom()` producing the target after any of the skips.
+#[derive(Debug, Clone)]
+pub struct SeedSearch {
+    skips:   Range<u64>,
+    matcher: Matcher,
+    scan:    Scan,
+}
+
+impl SeedSearch {
+    /// Fails right away when the solver finds the observations contradicting each other.
+    pub fn new(ty: StateType, skips: Range<u64>, target: &Target) -> Result<Self, Contradiction> {
+        Self::with_backend(ty, skips, target, Backend::default())
+    }
+
+    /// Same as `new()` with a choice of backend, only the solver can tell a contradiction.
+    pub fn with_backend(
+        ty: StateType,
+        skips: Range<u64>,
+        target: &Target,
+        backend: Backend,
+    ) -> Result<Self, Contradiction> {
+        let matcher = if backend == Backend::Kernel {
+            Matcher::Kernel(Arc::new(KernelCheck::new(ty, skips.clone(), target.clone())))
+        } else if skips.end - skips.start == 1 {
+            match solver::solve(ty, skips.start, target) {
+                Solution::Impossible(e) => return Err(e),
+                Solution::Seeds(seeds) => Matcher::Solved(seeds),
+                Solution::Search(pruned) => Matcher::Pruned(Arc::new(pruned)),
+            }
+        } else {
+            Matcher::Sliding(Arc::new(SlidingSearch::new(ty, skips.clone(), target.clone())))
+        };
+
+        //seed 0 is the same as seed 1
+        let scan = Scan::new(1..=u32::MAX as u64, Shard::default());
+        Ok(Self { skips, matcher, scan })
+    }
+
+    /// Seeds producing `pattern` right after `skip` calls, with up to `tolerance` positions
+    /// not fitting it.
+    pub fn with_pattern(ty: StateType, skip: u64, pattern: Pattern, tolerance: usize) -> Self {
+        let matcher = Matcher::Pattern(Arc::new(PatternCheck::new(ty, skip, pattern, tolerance)));
+        let scan = Scan::new(1..=u32::MAX as u64, Shard::default());
+        Self { skips: skip..skip + 1, matcher, scan }
+    }
+
+    /// Only the seeds of `shard`.
+    pub fn shard(self, shard: Shard) -> Self {
+        Self { scan: self.scan.with_shard(shard), ..self }
+    }
+
+    /// Only the seeds in `seeds`, shards keep their chunks.
+    pub fn seeds(self, seeds: RangeInclusive<u64>) -> Self {
+        Self { scan: self.scan.with_seeds(seeds), ..self }
+    }
+
+    /// Cancels the brute force once `max` matches are found.
+    pub fn max_matches(self, max: usize) -> Self {
+        Self { scan: self.scan.with_max_matches(max), ..self }
+    }
+
+    /// Same as `max_matches(1)`.
+    pub fn first(self) -> Self {
+        self.max_matches(1)
+    }
+
+    /// Whether there's any brute force to do.
+    pub fn is_solved(&self) -> bool {
+        matches!(self.matcher, Matcher::Solved(_))
+    }
+
+    /// Linear facts the solver found, see [`Pruned::facts`].
+    pub fn facts(&self) -> usize {
+        match &self.matcher {
+            Matcher::Pruned(pruned) => pruned.facts(),
+            _ => 0,
+        }
+    }
+
+    /// Matches along with their mismatches, the best first. Mismatches are only counted for
+    /// patterns, exact targets have none.
+    pub fn rank(&self, matches: &[Match]) -> Vec<(Match, usize)> {
+        let mut ranked: Vec<(Match, usize)> = matches
+            .iter()
+            .map(|&x| match &self.matcher {
+                Matcher::Pattern(check) => (x, check.mismatches(x.seed)),
+                _ => (x, 0),
+            })
+            .collect();
+        ranked.sort_by_key(|&(x, mismatches)| (mismatches, x));
+        ranked
+    }
+
+    fn solved(&self, seeds: &[u32]) -> Vec<Match> {
+        let mut matches: Vec<Match> = seeds
+            .iter()
+            .filter(|&&seed| self.scan.contains(seed as u64))
+            .map(|&seed| Match { seed, skip: self.skips.start })
+            .collect();
+        matches.truncate(self.scan.max_matches().unwrap_or(usize::MAX));
+        matches
+    }
+
+    /// Every match, sorted, see [`Scan::run`].
+    pub fn run(
+        &self,
+        checkpoint: Option<&Checkpoint>,
+        progress: impl Fn(usize, usize) + Sync,
+    ) -> io::Result<Vec<Match>> {
+        if let Matcher::Solved(seeds) = &self.matcher {
+            return Ok(self.solved(seeds));
+        }
+
+        let skip = self.skips.start;
+        let matches =
+            self.scan.run(checkpoint, progress, |seeds| self.matcher.matches(seeds, skip))?;
+        Ok(matches.into_iter().map(Match::from).collect())
+    }
+
+    /// Every match, sorted.
+    pub fn collect(&self) -> Vec<Match> {
+        // nothing can fail without a checkpoint
+        self.run(None, |_, _| {}).unwrap()
+    }
+
+    /// Matches among the candidates of `source` instead of all seeds, sorted, each with the
+    /// candidate it came from. The shard is ignored.
+    pub fn run_source(&self, source: &SeedSource) -> Vec<(Candidate, u64)> {
+        let max = self.scan.max_matches().unwrap_or(usize::MAX);
+        let found = AtomicUsize::new(0);
+
+        let mut matches: Vec<(Candidate, u64)> = source
+            .tasks()
+            .into_par_iter()
+            .flat_map_iter(|task| {
+                if found.load(Ordering::Relaxed) >= max {
+                    return vec![];
+                }
+
+                let candidates: Vec<Candidate> = source.candidates(task).collect();
+                // sorted, xor and the like only shuffle runs of consecutive seeds, which are
+                // checked LANES at a time
+                let mut seeds: Vec<u64> = candidates.iter().map(|x| x.seed as u64).collect();
+                seeds.sort_unstable();
+                seeds.dedup();
+                let hits: Vec<(u64, u64)> =
+                    self.matcher.matches(seeds.into_iter(), self.skips.start).collect();
+
+                // back to where the seeds came from, there are only a few hits
+                let matches: Vec<(Candidate, u64)> = hits
+                    .into_iter()
+                    .flat_map(|(seed, skip)| {
+                        candidates
+                            .iter()
+                            .filter(move |x| x.seed as u64 == seed)
+                            .map(move |&x| (x, skip))
+                    })
+                    .collect();
+                found.fetch_add(matches.len(), Ordering::Relaxed);
+                matches
+            })
+            .collect();
+
+        matches.sort_unstable();
+        matches.dedup();
+        matches.truncate(max);
+        matches
+    }
+
+    /// Matches as they are found, dropping the receiver cancels the search.
+    pub fn stream(&self) -> Receiver<Match> {
+        let (sender, receiver) = std::sync::mpsc::channel();
+
+        if let Matcher::Solved(seeds) = &self.matcher {
+            self.solved(seeds).into_iter().for_each(|x| sender.send(x).unwrap());
+            return receiver;
+        }
+
+        let (matcher, skip) = (self.matcher.clone(), self.skips.start);
+        let found = self.scan.clone().stream(move |seeds| matcher.matches(seeds, skip));
+        std::thread::spawn(move || {
+            for x in found {
+                if sender.send(Match::from(x)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        receiver
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        candidates::Formula,
+        glibc::GlibcRandom,
+        observation::{Mapping, DEFAULT_ALPHABET},
+        scan::CHUNK_SIZE,
+    };
+
+    fn target(seed: u32, skip: usize, len: usize) -> Target {
+        let mut rng = GlibcRandom::new(seed);
+        let outputs: Vec<u32> = (0..skip + len).map(|_| rng.rand() % 7).collect();
+        Target::new(Mapping::Modulo(7), outputs[skip..].to_vec()).unwrap()
+    }
+
+    // printed by a C program calling srandom(seed), random() skip times, then building the text
+    // as alphabet[random() % 62], on glibc 2.36
+    const KNOWN: [(u32, u64, &[u8]); 5] = [
+        (1_000_042, 1000, b"cfVtdVxHWx"),
+        (5_000_123, 0, b"WnlOJAHhjVm1"),
+        (2_147_483_653, 5, b"QzTR4CXlQx"),
+        (3_000_000_007, 99_999_990, b"arYjW7ozId"),
+        (4_294_967_295, 0, b"P8q58ooj"),
+    ];
+
+    #[test]
+    fn known_seeds() {
+        for &backend in Backend::ALL.iter() {
+            for &(seed, skip, text) in KNOWN.iter() {
+                let target = Target::from_text(text, DEFAULT_ALPHABET).unwrap();
+                let seeds = seed as u64 - 300..=(seed as u64 + 300).min(u32::MAX as u64);
+                let search = SeedSearch::with_backend(
+                    StateType::default(),
+                    skip..skip + 1,
+                    &target,
+                    backend,
+                )
+                .unwrap()
+                .seeds(seeds.clone());
+                assert_eq!(search.collect(), [Match { seed, skip }], "{:?}", backend);
+
+                // the skip found among others
+                let skips = skip.saturating_sub(2)..skip + 3;
+                let search =
+                    SeedSearch::with_backend(StateType::default(), skips, &target, backend)
+                        .unwrap()
+                        .seeds(seeds);
+                assert_eq!(search.collect(), [Match { seed, skip }], "{:?}", backend);
+            }
+        }
+    }
+
+    #[test]
+    fn finds_the_seed_and_stops() {
+        let (seed, skip) = (3 * CHUNK_SIZE as u32 + 12_345, 50);
+        let search = SeedSearch::new(StateType::default(), skip..skip + 1, &target(seed, 50, 16))
+            .unwrap()
+            .seeds(seed as u64 - 20_000..=seed as u64 + 20_000)
+            .shard(Shard { index: 3, count: 256 });
+        assert_eq!(search.collect(), [Match { seed, skip }]);
+        assert_eq!(search.clone().first().stream().recv(), Ok(Match { seed, skip }));
+
+        let other = search.shard(Shard { index: 4, count: 256 });
+        assert_eq!(other.stream().iter().count(), 0);
+    }
+
+    #[test]
+    fn candidates_only() {
+        let (time, pid) = (1_700_000_123, 31_337);
+        let source = SeedSource {
+            formulas: vec![
+                Formula::parse("t").unwrap(),
+                Formula::parse("t ^ p").unwrap(),
+                Formula::parse("t + p").unwrap(),
+            ],
+            times:    time - 300..=time + 300,
+            pids:     31_200..=31_400,
+        };
+
+        let search =
+            SeedSearch::new(StateType::default(), 5..6, &target(time ^ pid, 5, 16)).unwrap();
+        let matches = search.run_source(&source);
+        // other times and PIDs xor to the same seed
+        assert!(matches.contains(&(Candidate { seed: time ^ pid, time, pid, formula: 1 }, 5)));
+        assert!(matches.iter().all(|(x, skip)| x.seed == time ^ pid && *skip == 5));
+    }
+
+    #[test]
+    fn ranks_by_mismatches() {
+        let seed = 2_000_321;
+        let mut rng = GlibcRandom::new(seed);
+        let mut text: Vec<u8> = (0..12).map(|_| b"0123456789"[rng.rand() as usize % 10]).collect();
+        text[1] = b'?';
+        text[6] = if text[6] == b'0' { b'1' } else { b'0' };
+
+        let pattern = Pattern::from_text(&text, b"0123456789").unwrap();
+        let search = SeedSearch::with_pattern(StateType::default(), 0, pattern, 2)
+            .seeds(2_000_000..=2_000_400);
+        let ranked = search.rank(&search.collect());
+
+        assert_eq!(ranked[0], (Match { seed, skip: 0 }, 1));
+        assert!(ranked.windows(2).all(|x| x[0].1 <= x[1].1));
+        assert!(ranked.iter().all(|x| x.1 <= 2));
+    }
+
+    #[test]
+    fn solved_without_scan() {
+        let mut rng = GlibcRandom::new(77);
+        let outputs: Vec<u32> = (0..400).map(|_| rng.random()).collect();
+        let target = Target::new(Mapping::Identity, outputs).unwrap();
+
+        let search = SeedSearch::new(StateType::default(), 0..1, &target).unwrap();
+        assert!(search.is_solved());
+        assert_eq!(search.collect(), [Match { seed: 77, skip: 0 }]);
+        assert_eq!(search.stream().iter().collect::<Vec<_>>(), [Match { seed: 77, skip: 0 }]);
+    }
+}
@@ -0,0 +1,62 @@
+//! Seed search when only a range of skipped calls is known.
+//!
+//! Instead of running the fixed-skip search once per skip, every seed jumps to the start of the
+//! range once and then runs the generator through it, so consecutive skips share all the work.
+
+use crate::{forward::Jump, glibc::StateType, observation::Target};
+use std::ops::Range;
+
+#[derive(Debug, Clone)]
+pub struct SlidingSearch {
+    jump:   Jump,
+    skips:  Range<u64>,
+    target: Target,
+}
+
+impl SlidingSearch {
+    pub fn new(ty: StateType, skips: Range<u64>, target: Target) -> Self {
+        Self { jump: Jump::new(ty, skips.start), skips, target }
+    }
+
+    /// Skips from the range after which `seed` produces the target.
+    ///
+    /// `outputs` is scratch space, so it can be reused between seeds.
+    pub fn skips(&self, seed: u32, outputs: &mut Vec<u32>) -> Vec<u64> {
+        let mut rng = self.jump.state(seed);
+        let len = self.target.len();
+        let mapping = self.target.mapping();
+        let windows = (self.skips.end - self.skips.start) as usize;
+
+        outputs.clear();
+        outputs.extend((0..windows + len - 1).map(|_| mapping.apply(rng.random())));
+
+        outputs
+            .windows(len)
+            .enumerate()
+            .filter(|(_, window)| *window == self.target.values())
+            .map(|(i, _)| self.skips.start + i as u64)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{glibc::GlibcRandom, observation::Mapping};
+
+    #[test]
+    fn finds_every_skip() {
+        let mut rng = GlibcRandom::new(7);
+        let outputs: Vec<u32> = (0..1000).map(|_| rng.rand() % 4).collect();
+
+        let target = Target::new(Mapping::Modulo(4), outputs[500..506].to_vec()).unwrap();
+        let search = SlidingSearch::new(StateType::default(), 100..900, target.clone());
+
+        let expected: Vec<u64> = (100..900)
+            .filter(|&i| &outputs[i as usize..i as usize + 6] == target.values())
+            .collect();
+
+        assert!(expected.contains(&500));
+        assert_eq!(search.skips(7, &mut vec![]), expected);
+    }
+}
@@ -0,0 +1,407 @@
+//! Constraint solver for reduced observations such as `rand() % n` or `rand() / (RAND_MAX / n)`.
+//!
+//! Every observation tells an interval the 31-bit output lies in and, for even moduli, its lowest
+//! bits. Consecutive outputs of the additive generators are tied by
+//! `o[i] = o[i - deg] + o[i - sep] + c` (mod 2^31), so the intervals get tightened against each
+//! other, and the low bits expose the carries. A carry is the AND of two dropped low bits, which
+//! the forward matrix taken mod 2 turns into linear facts about the low bits of the seeded state:
+//! seeds failing them are rejected with a couple of popcounts, before any dot product.
+//!
+//! When the outputs are full and there are enough of them, the state is recovered without the
+//! seed (see [`crate::predict`]) and the forward matrix is inverted, so no brute force is needed.
+
+use crate::{
+    batch::{self, Check, SeedStates, LANES},
+    forward::ForwardMatrix,
+    glibc::{seed_state, StateType, MAX_DEGREE, RAND_MAX},
+    observation::{Mapping, Target},
+    predict::{Contradiction, Predictor, System},
+};
+
+const OUTPUTS: i64 = 1 << 31;
+
+/// What a single observation says about the output it came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Constraint {
+    mapping:  Mapping,
+    value:    u32,
+    /// output lies in `lo..=hi`
+    pub lo:   u32,
+    pub hi:   u32,
+    /// number of known lowest bits of the output
+    pub bits: u32,
+    pub low:  u32,
+}
+
+impl Constraint {
+    pub fn new(mapping: Mapping, value: u32) -> Self {
+        let (lo, hi, bits, low) = match mapping {
+            Mapping::Identity => (value, value, 31, value),
+            Mapping::Modulo(n) => {
+                // x % n keeps x % 2^k for every 2^k dividing n
+                let bits = n.trailing_zeros().min(31);
+                (value, RAND_MAX, bits, value & ((1 << bits) - 1))
+            }
+            Mapping::Scale(n) => {
+                let step = RAND_MAX / n;
+                (value * step, (value * step).saturating_add(step - 1).min(RAND_MAX), 0, 0)
+            }
+            Mapping::HighBits(k) => {
+                let shift = 31 - k;
+                (value << shift, (value << shift) | ((1 << shift) - 1), 0, 0)
+            }
+        };
+
+        Self { mapping, value, lo, hi, bits, low }
+    }
+
+    pub fn admits(&self, output: u32) -> bool {
+        self.lo <= output && output <= self.hi && self.mapping.apply(output) == self.value
+    }
+
+    /// Rough fraction of outputs admitted, the smaller the earlier it's worth checking.
+    fn selectivity(&self) -> f64 {
+        let width = (self.hi - self.lo) as f64 + 1.;
+        let modulus = match self.mapping {
+            Mapping::Modulo(n) => n as f64,
+            _ => 1.,
+        };
+
+        width / modulus / OUTPUTS as f64
+    }
+
+    /// Shrinks the interval to its intersection with `lo..=hi` taken mod 2^31.
+    /// `false` if nothing is left.
+    fn tighten(&mut self, lo: i64, hi: i64) -> bool {
+        if hi - lo + 1 >= OUTPUTS {
+            return true;
+        }
+
+        let shift = lo.div_euclid(OUTPUTS) * OUTPUTS;
+        let (lo, hi) = (lo - shift, hi - shift);
+
+        // the range wraps around at most once
+        let pieces = [(lo, hi.min(OUTPUTS - 1)), (0, hi - OUTPUTS)];
+        let parts: Vec<(i64, i64)> = pieces
+            .iter()
+            .map(|&(a, b)| (a.max(self.lo as i64), b.min(self.hi as i64)))
+            .filter(|(a, b)| a <= b)
+            .collect();
+
+        match (parts.iter().map(|x| x.0).min(), parts.iter().map(|x| x.1).max()) {
+            (Some(lo), Some(hi)) => {
+                self.lo = lo as u32;
+                self.hi = hi as u32;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Solution {
+    /// No seed can produce the observations.
+    Impossible(Contradiction),
+    /// Found without brute force.
+    Seeds(Vec<u32>),
+    /// What's left for the brute force.
+    Search(Pruned),
+}
+
+/// Per-seed check with everything the solver managed to derive.
+#[derive(Debug, Clone)]
+pub struct Pruned {
+    forward:     ForwardMatrix,
+    constraints: Vec<Constraint>,
+    // most selective first
+    order:       Vec<usize>,
+    // parity(mask & low bits of the input) == value
+    facts:       Vec<(u64, bool)>,
+    // parities that aren't both set
+    exclusions:  Vec<(u64, u64)>,
+}
+
+fn parity(x: u64) -> bool {
+    x.count_ones() & 1 == 1
+}
+
+impl Pruned {
+    pub fn constraints(&self) -> &[Constraint] {
+        &self.constraints
+    }
+
+    /// Number of independent linear facts, each halves the seeds reaching the dot products.
+    pub fn facts(&self) -> usize {
+        self.facts.len()
+    }
+
+    pub fn state_type(&self) -> StateType {
+        self.forward.state_type()
+    }
+
+    /// Lanes that pass the most selective constraints, as a bit mask. The rest of the checks is
+    /// left to `matches()`.
+    pub fn lanes(&self, states: &SeedStates) -> u32 {
+        let ty = self.forward.state_type();
+        let mut mask = u32::MAX >> (32 - LANES);
+
+        for &i in self.order.iter().take(2) {
+            let raw = batch::dot(&self.forward.rows()[i], states.words());
+            for (lane, &x) in raw.iter().enumerate() {
+                if !self.constraints[i].admits(ty.output(x)) {
+                    mask &= !(1 << lane);
+                }
+            }
+            if mask == 0 {
+                break;
+            }
+        }
+
+        mask
+    }
+
+    pub fn matches(&self, seed: u32) -> bool {
+        let ty = self.forward.state_type();
+        let input = ForwardMatrix::input(ty, seed);
+
+        if !self.facts.is_empty() || !self.exclusions.is_empty() {
+            let lows =
+                input.iter().enumerate().fold(0u64, |acc, (i, x)| acc | (*x as u64 & 1) << i);
+
+            if self.facts.iter().any(|&(mask, value)| parity(mask & lows) != value) {
+                return false;
+            }
+            if self.exclusions.iter().any(|&(a, b)| parity(a & lows) && parity(b & lows)) {
+                return false;
+            }
+        }
+
+        self.order
+            .iter()
+            .all(|&i| self.constraints[i].admits(ty.output(self.forward.raw(i, &input))))
+    }
+}
+
+impl Check for Pruned {
+    fn state_type(&self) -> StateType {
+        Pruned::state_type(self)
+    }
+
+    fn lanes(&self, states: &SeedStates) -> u32 {
+        Pruned::lanes(self, states)
+    }
+
+    fn matches(&self, seed: u32) -> bool {
+        Pruned::matches(self, seed)
+    }
+}
+
+/// Seeds whose state is `window` right before `random()` call number `calls` after `srandom()`.
+pub fn seeds_from_state(ty: StateType, calls: u64, window: &[u32; MAX_DEGREE]) -> Vec<u32> {
+    let jump = ForwardMatrix::transition(ty).pow(ty.discard() as u64 + calls);
+    let back = jump.inverse().expect("random() is a bijection");
+
+    if ty.is_lcg() {
+        // only the low 31 bits of the seed matter
+        let seed = back.mul_vec(&[1, window[0]])[1] & RAND_MAX;
+        return [seed, seed | 1 << 31].iter().copied().filter(|&x| x != 0).collect();
+    }
+
+    let degree = ty.degree();
+    let input = back.mul_vec(&window[..degree]);
+    let seed = input[0];
+
+    // the state may well be unreachable from srandom()
+    if seed != 0 && seed_state(ty, seed)[..degree] == input[..] {
+        vec![seed]
+    } else {
+        vec![]
+    }
+}
+
+/// Tightens intervals of consecutive outputs against each other until nothing changes.
+fn propagate_intervals(ty: StateType, constraints: &mut [Constraint]) -> Result<(), usize> {
+    let (degree, separation) = (ty.degree(), ty.separation());
+    let lo_hi = |c: Constraint| (c.lo as i64, c.hi as i64);
+
+    loop {
+        let before = constraints.to_vec();
+
+        for i in degree..constraints.len() {
+            let (far, near) = (constraints[i - degree], constraints[i - separation]);
+            let (i_lo, i_hi) = lo_hi(constraints[i]);
+            let (f_lo, f_hi) = lo_hi(far);
+            let (n_lo, n_hi) = lo_hi(near);
+
+            if !constraints[i].tighten(f_lo + n_lo, f_hi + n_hi + 1)
+                || !constraints[i - degree].tighten(i_lo - n_hi - 1, i_hi - n_lo)
+                || !constraints[i - separation].tighten(i_lo - f_hi - 1, i_hi - f_lo)
+            {
+                return Err(i);
+            }
+        }
+
+        if before[..] == constraints[..] {
+            return Ok(());
+        }
+    }
+}
+
+pub fn solve(ty: StateType, skip: u64, target: &Target) -> Solution {
+    let values = target.values();
+    let impossible =
+        |index: usize| Solution::Impossible(Contradiction { index, value: values[index] });
+
+    if target.mapping() == Mapping::Identity {
+        let mut predictor = Predictor::new(ty);
+        for &x in values.iter() {
+            if let Err(e) = predictor.push(x) {
+                return Solution::Impossible(e);
+            }
+        }
+
+        if let Some(rng) = predictor.state() {
+            return Solution::Seeds(seeds_from_state(ty, skip + values.len() as u64, &rng.state()));
+        }
+    }
+
+    let mut constraints: Vec<Constraint> =
+        values.iter().map(|&x| Constraint::new(target.mapping(), x)).collect();
+    let forward = ForwardMatrix::new(ty, skip, values.len());
+
+    let mut system = System::default();
+    let mut exclusions = vec![];
+
+    if !ty.is_lcg() {
+        if let Err(i) = propagate_intervals(ty, &mut constraints) {
+            return impossible(i);
+        }
+
+        // low bit of word i is parity(mask & low bits of the input)
+        let masks: Vec<u64> = forward
+            .rows()
+            .iter()
+            .map(|row| row.iter().enumerate().fold(0, |acc, (j, x)| acc | (*x as u64 & 1) << j))
+            .collect();
+
+        let (degree, separation) = (ty.degree(), ty.separation());
+        for i in degree..constraints.len() {
+            let (far, near) = (i - degree, i - separation);
+            let bits = constraints[i].bits.min(constraints[far].bits).min(constraints[near].bits);
+            if bits == 0 {
+                continue;
+            }
+
+            let sum = constraints[far].low.wrapping_add(constraints[near].low);
+            match constraints[i].low.wrapping_sub(sum) & ((1 << bits) - 1) {
+                0 => exclusions.push((masks[far], masks[near])),
+                1 => {
+                    if !(system.add(masks[far], true) && system.add(masks[near], true)) {
+                        return impossible(i);
+                    }
+                }
+                _ => return impossible(i),
+            }
+        }
+    }
+
+    let mut order: Vec<usize> = (0..constraints.len()).collect();
+    order.sort_by(|&a, &b| {
+        constraints[a].selectivity().partial_cmp(&constraints[b].selectivity()).unwrap()
+    });
+
+    Solution::Search(Pruned {
+        forward,
+        constraints,
+        order,
+        facts: system.equations().to_vec(),
+        exclusions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::glibc::GlibcRandom;
+
+    fn outputs(ty: StateType, seed: u32, skip: u64, count: usize) -> Vec<u32> {
+        let mut rng = GlibcRandom::with_type(ty, seed);
+        (0..skip).for_each(|_| {
+            rng.random();
+        });
+        (0..count).map(|_| rng.random()).collect()
+    }
+
+    #[test]
+    fn full_outputs_need_no_search() {
+        for &ty in StateType::ALL.iter() {
+            for &seed in [1, 42, 3_000_000_000].iter() {
+                let values = outputs(ty, seed, 1000, 600);
+                let target = Target::new(Mapping::Identity, values).unwrap();
+
+                match solve(ty, 1000, &target) {
+                    Solution::Seeds(seeds) => assert!(seeds.contains(&seed), "{:?}", ty),
+                    other => panic!("{:?}, seed {}: {:?}", ty, seed, other),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pruning_keeps_matching_seeds() {
+        let ty = StateType::Type3;
+        let mapping = Mapping::Modulo(8);
+        let values: Vec<u32> =
+            outputs(ty, 4242, 5, 300).iter().map(|&x| mapping.apply(x)).collect();
+        let target = Target::new(mapping, values).unwrap();
+
+        let pruned = match solve(ty, 5, &target) {
+            Solution::Search(pruned) => pruned,
+            other => panic!("{:?}", other),
+        };
+        assert!(pruned.facts() > 0);
+        assert!(pruned.matches(4242));
+
+        let forward = ForwardMatrix::new(ty, 5, target.len());
+        for seed in 1..3000 {
+            assert_eq!(pruned.matches(seed), target.matches(forward.outputs(seed)), "{}", seed);
+        }
+    }
+
+    #[test]
+    fn intervals_get_tighter() {
+        let ty = StateType::Type1;
+        let mapping = Mapping::Scale(16);
+        let values: Vec<u32> = outputs(ty, 7, 0, 100).iter().map(|&x| mapping.apply(x)).collect();
+        let target = Target::new(mapping, values.clone()).unwrap();
+
+        let pruned = match solve(ty, 0, &target) {
+            Solution::Search(pruned) => pruned,
+            other => panic!("{:?}", other),
+        };
+        assert!(pruned.matches(7));
+
+        let tightened = pruned
+            .constraints()
+            .iter()
+            .zip(values.iter())
+            .filter(|(c, &x)| *c != &Constraint::new(mapping, x))
+            .count();
+        assert!(tightened > 0);
+    }
+
+    #[test]
+    fn impossible() {
+        let ty = StateType::Type3;
+        let mut values: Vec<u32> = outputs(ty, 1, 0, 40).iter().map(|x| x % 4).collect();
+        // o[35] = o[4] + o[32] + carry, so this one is off by two
+        values[35] = (values[35] + 2) % 4;
+
+        let target = Target::new(Mapping::Modulo(4), values).unwrap();
+        match solve(ty, 0, &target) {
+            Solution::Impossible(e) => assert_eq!(e.index, 35),
+            other => panic!("{:?}", other),
+        }
+    }
+}
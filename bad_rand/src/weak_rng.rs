@@ -0,0 +1,52 @@
+//! Common interface of the generators this crate knows how to crack.
+
+use std::ops::RangeInclusive;
+
+use rayon::prelude::*;
+
+use crate::observation::Target;
+
+/// A seedable generator with small enough state or seed space to be recovered from its outputs.
+pub trait WeakRng: Clone + Send + Sync + Sized {
+    /// Name used on the command line.
+    const NAME: &'static str;
+    /// Largest output.
+    const MAX: u32;
+
+    /// Seeds worth a brute force.
+    fn seeds() -> RangeInclusive<u64>;
+
+    fn from_seed(seed: u64) -> Self;
+
+    fn next(&mut self) -> u32;
+
+    fn skip(&mut self, n: u64) {
+        (0..n).for_each(|_| {
+            self.next();
+        });
+    }
+
+    /// Seeds that produce `target` after `skip` outputs, sorted.
+    ///
+    /// `target` must be built for `Self::MAX`. By default it's [`brute_force`], generators with
+    /// some structure to exploit do better.
+    fn crack(skip: u64, target: &Target) -> Vec<u64> {
+        brute_force::<Self>(skip, target)
+    }
+
+    /// Whether `target` comes out after `skip` outputs.
+    fn matches(mut self, skip: u64, target: &Target) -> bool {
+        self.skip(skip);
+        target.matches(std::iter::repeat_with(|| self.next()))
+    }
+}
+
+/// Tries every seed of `R::seeds()` in parallel.
+pub fn brute_force<R: WeakRng>(skip: u64, target: &Target) -> Vec<u64> {
+    let mut seeds: Vec<u64> = R::seeds()
+        .into_par_iter()
+        .filter(|&seed| R::from_seed(seed).matches(skip, target))
+        .collect();
+    seeds.sort_unstable();
+    seeds
+}
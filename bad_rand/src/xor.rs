@@ -0,0 +1,122 @@
+//! Buffers "encrypted" with `buf[i] ^= rand() & 0xff`.
+//!
+//! A known piece of the plaintext gives the keystream bytes at its offset, which are
+//! `rand() % 256` observations, so the seed search works on them as on any other target.
+//! The seed then gives the whole keystream back.
+
+use std::{fmt, ops::Range};
+
+use crate::{
+    glibc::{GlibcRandom, StateType},
+    observation::{Mapping, Target, TargetError},
+    predict::Contradiction,
+    search::{Match, SeedSearch},
+};
+
+/// Ciphertext along with a piece of its plaintext.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KnownPlaintext<'a> {
+    pub ciphertext: &'a [u8],
+    pub plaintext:  &'a [u8],
+    /// where `plaintext` starts in the ciphertext
+    pub offset:     usize,
+}
+
+impl KnownPlaintext<'_> {
+    /// Keystream bytes under the known plaintext as `rand() % 256` observations, fails if the
+    /// plaintext is empty or runs past the end of the ciphertext.
+    pub fn target(&self) -> Result<Target, TargetError> {
+        let end = self.offset.saturating_add(self.plaintext.len());
+        let ciphertext = self.ciphertext.get(self.offset..end).unwrap_or(&[]);
+        let keystream = ciphertext.iter().zip(self.plaintext).map(|(x, y)| (x ^ y) as u32);
+
+        Target::new(Mapping::Modulo(256), keystream.collect())
+    }
+
+    /// Seeds encrypting the plaintext, with `skips` being the `rand()` calls made before the
+    /// first byte of the buffer rather than before the known plaintext.
+    pub fn search(
+        &self,
+        ty: StateType,
+        skips: Range<u64>,
+    ) -> Result<SeedSearch, KnownPlaintextError> {
+        let target = self.target().map_err(KnownPlaintextError::Target)?;
+        let offset = self.offset as u64;
+
+        SeedSearch::new(ty, skips.start + offset..skips.end + offset, &target)
+            .map_err(KnownPlaintextError::Contradiction)
+    }
+
+    /// `rand()` calls made before the first byte of the buffer for a match of `search()`.
+    pub fn skip(&self, found: Match) -> u64 {
+        found.skip - self.offset as u64
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KnownPlaintextError {
+    Target(TargetError),
+    Contradiction(Contradiction),
+}
+
+impl fmt::Display for KnownPlaintextError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            KnownPlaintextError::Target(TargetError::Empty) => {
+                write!(f, "the known plaintext is empty or lies past the end of the ciphertext")
+            }
+            KnownPlaintextError::Target(e) => write!(f, "{}", e),
+            KnownPlaintextError::Contradiction(e) => write!(f, "no seed encrypts it so: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for KnownPlaintextError {}
+
+/// XORs `data` with `rand() & 0xff` of `rng`, which both encrypts and decrypts.
+pub fn apply_keystream(rng: &mut GlibcRandom, data: &mut [u8]) {
+    for x in data.iter_mut() {
+        *x ^= (rng.rand() & 0xff) as u8;
+    }
+}
+
+/// Plaintext of a buffer encrypted after `srandom(seed)` and `skip` other calls.
+pub fn decrypt(ty: StateType, seed: u32, skip: u64, ciphertext: &[u8]) -> Vec<u8> {
+    let mut rng = GlibcRandom::with_type(ty, seed);
+    rng.skip(skip);
+
+    let mut plaintext = ciphertext.to_vec();
+    apply_keystream(&mut rng, &mut plaintext);
+    plaintext
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decrypts_from_a_fragment() {
+        let (seed, skip) = (2_000_123, 7);
+        let plaintext =
+            b"GET /admin HTTP/1.1\r\nHost: example.com\r\nCookie: session=hunter2\r\n\r\n";
+        let ciphertext = decrypt(StateType::default(), seed, skip, plaintext);
+        assert_ne!(&ciphertext[..], &plaintext[..]);
+
+        let known = KnownPlaintext {
+            ciphertext: &ciphertext,
+            plaintext:  b"Host: example.com",
+            offset:     21,
+        };
+        let search = known
+            .search(StateType::default(), skip..skip + 1)
+            .unwrap()
+            .seeds(2_000_000..=2_000_200);
+        let found = search.collect();
+        assert_eq!(found, [Match { seed, skip: skip + 21 }]);
+        assert_eq!(known.skip(found[0]), skip);
+        assert_eq!(decrypt(StateType::default(), seed, skip, &ciphertext), &plaintext[..]);
+
+        let past = KnownPlaintext { offset: ciphertext.len() - 3, ..known };
+        assert_eq!(past.target(), Err(TargetError::Empty));
+    }
+}
@@ -0,0 +1,207 @@
+//! The hull algorithms behind a common trait, so they can be swapped and compared. They all
+//! return exactly what [`convex_hull_with`] does.
+//!
+//! The Graham scan and the monotone chain sort the points, `O(n log n)` whatever the hull.
+//! QuickHull and Chan's algorithm spend less the fewer vertices there are, Chan's guaranteeing
+//! `O(n log h)` and QuickHull only on average. With points on the edges kept, every point is
+//! then located on the hull, `O(n log h)` again.
+
+use std::cmp::Ordering;
+
+use crate::{
+    convex::{along, locate, Location},
+    hull::{convex_hull_with, lowest, same, Collinear, Entry},
+    point::{Coordinate, Coordinates},
+};
+
+/// A convex hull algorithm.
+pub trait HullAlgorithm {
+    const NAME: &'static str;
+
+    /// Same as [`convex_hull_with`].
+    fn hull<P: Coordinates>(points: &[P], collinear: Collinear) -> Vec<usize>;
+
+    /// Same as [`hull`](Self::hull), for points that can be shared between threads. Only
+    /// [`ParallelChain`](crate::ParallelChain) makes use of that, the others call `hull`.
+    fn par_hull<P>(points: &[P], collinear: Collinear) -> Vec<usize>
+    where
+        P: Coordinates + Sync,
+        P::Coordinate: Send + Sync,
+    {
+        Self::hull(points, collinear)
+    }
+}
+
+pub struct GrahamScan;
+
+impl HullAlgorithm for GrahamScan {
+    const NAME: &'static str = "Graham scan";
+
+    fn hull<P: Coordinates>(points: &[P], collinear: Collinear) -> Vec<usize> {
+        convex_hull_with(points, collinear)
+    }
+}
+
+/// The result given the vertices of the hull, which is what every algorithm works out first:
+/// strictly convex and counter-clockwise, starting anywhere, and each the first of its
+/// duplicates, i.e. the one with the lowest index among the points with its coordinates.
+pub(crate) fn finish<P: Coordinates>(
+    points: &[P],
+    vertices: Vec<Entry<P::Coordinate>>,
+    collinear: Collinear,
+) -> Vec<usize> {
+    let entries = points.iter().enumerate().map(|(i, p)| ([p.x(), p.y()], i));
+    finish_entries(entries, vertices, collinear)
+}
+
+/// Same as [`finish`], with the points given along with their indices, in any order.
+pub(crate) fn finish_entries<C: Coordinate>(
+    points: impl Iterator<Item = Entry<C>>,
+    vertices: Vec<Entry<C>>,
+    collinear: Collinear,
+) -> Vec<usize> {
+    finish_located(vertices, collinear, |v| {
+        let mut located: Vec<_> = points.filter_map(|entry| on_edge(v, entry)).collect();
+        located.sort_by(|a, b| by_edge(v, a, b));
+        located
+    })
+}
+
+/// The edge of the polygon `v` the point is strictly inside of, if any, along with the point.
+pub(crate) fn on_edge<C: Coordinate>(v: &[[C; 2]], (p, i): Entry<C>) -> Option<(usize, Entry<C>)> {
+    match locate(v, &p) {
+        Location::Edge(k) => Some((k, (p, i))),
+        _ => None,
+    }
+}
+
+/// Points on the edges of `v` by edge, then going along it, then by index.
+pub(crate) fn by_edge<C: Coordinate>(
+    v: &[[C; 2]],
+    (k, (a, i)): &(usize, Entry<C>),
+    (l, (b, j)): &(usize, Entry<C>),
+) -> Ordering {
+    k.cmp(l).then_with(|| along(&v[*k], a, b)).then(i.cmp(j))
+}
+
+/// Same as [`finish`], with the points that are on the edges given by `on_edges` from the
+/// vertices, as [`on_edge`] finds them and sorted by [`by_edge`].
+pub(crate) fn finish_located<C: Coordinate>(
+    mut vertices: Vec<Entry<C>>,
+    collinear: Collinear,
+    on_edges: impl FnOnce(&[[C; 2]]) -> Vec<(usize, Entry<C>)>,
+) -> Vec<usize> {
+    let start = (0..vertices.len()).min_by(|&a, &b| lowest(&vertices[a].0, &vertices[b].0));
+    vertices.rotate_left(start.unwrap_or(0));
+    if collinear == Collinear::Drop || vertices.len() < 2 {
+        return vertices.iter().map(|&(_, i)| i).collect();
+    }
+
+    // all on a line is a segment, its only edge going from the lowest point
+    let (v, first): (Vec<_>, Vec<_>) = vertices.into_iter().unzip();
+    let mut located = on_edges(&v);
+    located.dedup_by(|(_, (b, _)), (_, (a, _))| same(a, b));
+
+    let mut hull = Vec::with_capacity(first.len() + located.len());
+    let mut located = located.into_iter().peekable();
+    for (k, &i) in first.iter().enumerate() {
+        hull.push(i);
+        while let Some((_, (_, j))) = located.next_if(|&(l, _)| l == k) {
+            hull.push(j);
+        }
+    }
+    hull
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        chain::MonotoneChain, chan::Chan, parallel::ParallelChain, quickhull::QuickHull,
+        testing::uniform,
+    };
+
+    fn agrees<A: HullAlgorithm, P>(points: &[P])
+    where
+        P: Coordinates + Sync,
+        P::Coordinate: Send + Sync,
+    {
+        for &collinear in [Collinear::Drop, Collinear::Keep].iter() {
+            let expected = GrahamScan::hull(points, collinear);
+            assert_eq!(A::hull(points, collinear), expected, "{}, {:?}", A::NAME, collinear);
+            assert_eq!(A::par_hull(points, collinear), expected, "{}, {:?}", A::NAME, collinear);
+        }
+    }
+
+    fn all_agree<P>(points: &[P])
+    where
+        P: Coordinates + Sync,
+        P::Coordinate: Send + Sync,
+    {
+        agrees::<MonotoneChain, P>(points);
+        agrees::<QuickHull, P>(points);
+        agrees::<Chan, P>(points);
+        agrees::<ParallelChain, P>(points);
+    }
+
+    #[test]
+    fn degenerate() {
+        let empty: [(f64, f64); 0] = [];
+        all_agree(&empty);
+        all_agree(&[(1., 1.)]);
+        all_agree(&[(1., 1.), (1., 1.), (1., 1.)]);
+        all_agree(&[(0., 0.), (2., 0.), (1., 0.), (2., 2.), (2., 0.), (0., 2.), (0., 1.)]);
+        all_agree(&[(2., 2.), (0., 0.), (1., 1.), (3., 3.), (1., 1.)]);
+        all_agree(&[(5., 3.), (5., -1.), (5., 7.)]);
+        all_agree(&[(3, -3), (0, 0), (1, -1), (-2, 2), (1, -1)]);
+        all_agree(&[(0., 0.), (0., 2.), (0., 1.), (1., 1.), (1., 0.), (1., 3.), (0., 3.)]);
+    }
+
+    #[test]
+    fn random() {
+        for seed in 0..20 {
+            let mut next = uniform(seed);
+            let n = [10, 100, 2000][seed as usize % 3];
+            let points: Vec<(f64, f64)> = (0..n).map(|_| (next(), next())).collect();
+            all_agree(&points);
+        }
+    }
+
+    #[test]
+    fn small_grid() {
+        // lots of duplicates and points on the edges
+        for seed in 0..50 {
+            let mut next = uniform(seed);
+            let size = [3., 5., 20.][seed as usize % 3];
+            let n = [5, 30, 500][seed as usize / 3 % 3];
+            let points: Vec<(i64, i64)> =
+                (0..n).map(|_| ((next() * size) as i64, (next() * size) as i64)).collect();
+            all_agree(&points);
+        }
+    }
+
+    #[test]
+    fn frame_and_circle() {
+        let mut next = uniform(5);
+        let frame: Vec<(f64, f64)> = (0..3000)
+            .map(|i| {
+                let (x, side) = (next(), (i / 2 % 2) as f64);
+                if i % 2 == 0 {
+                    (x, side)
+                } else {
+                    (side, x)
+                }
+            })
+            .collect();
+        all_agree(&frame);
+
+        // most points are vertices
+        let circle: Vec<(i64, i64)> = (0..1000)
+            .map(|_| {
+                let angle = next() * std::f64::consts::PI * 2.;
+                ((angle.cos() * 1e12) as i64, (angle.sin() * 1e12) as i64)
+            })
+            .collect();
+        all_agree(&circle);
+    }
+}
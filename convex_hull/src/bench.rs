@@ -0,0 +1,127 @@
+//! Timing the algorithms against each other. How many vertices the hull has is what tells them
+//! apart, so the distributions go from a handful (`Frame`, `Square`) to every point (`Circle`).
+
+use rand::distributions::Uniform;
+use rand::{rngs::ThreadRng, thread_rng, Rng};
+use std::time::Instant;
+
+use crate::{
+    algorithm::{GrahamScan, HullAlgorithm},
+    chain::MonotoneChain,
+    chan::Chan,
+    hull::Collinear,
+    parallel::ParallelChain,
+    quickhull::QuickHull,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, enum_map::Enum)]
+pub enum Distribution {
+    /// On the sides of the unit square.
+    Frame,
+    /// Inside the unit square.
+    Square,
+    /// On the unit circle.
+    Circle,
+}
+
+impl Distribution {
+    pub const ALL: [Distribution; 3] =
+        [Distribution::Frame, Distribution::Square, Distribution::Circle];
+
+    pub fn points(self, n: usize) -> Vec<(f64, f64)> {
+        let mut rng = thread_rng();
+        let uniform = Uniform::new(0.0, 1.0);
+
+        let generators = enum_map::enum_map! {
+            Distribution::Square => gen_square,
+            Distribution::Frame => gen_frame,
+            Distribution::Circle => gen_circle
+        };
+
+        (0..n).map(|_| generators[self](&mut rng, &uniform)).collect()
+    }
+}
+
+fn gen_frame(rng: &mut ThreadRng, uniform: &Uniform<f64>) -> (f64, f64) {
+    let x: f64 = rng.sample(uniform);
+    let y: u8 = rng.gen_range(0, 2);
+    let y = y as f64;
+
+    if rng.gen_bool(0.5) {
+        (x, y)
+    } else {
+        (y, x)
+    }
+}
+
+fn gen_square(rng: &mut ThreadRng, uniform: &Uniform<f64>) -> (f64, f64) {
+    (rng.sample(uniform), rng.sample(uniform))
+}
+
+fn gen_circle(rng: &mut ThreadRng, uniform: &Uniform<f64>) -> (f64, f64) {
+    //smell
+    let angle = rng.sample(uniform) * std::f64::consts::PI * 2.;
+
+    let x = angle.cos();
+    let y = angle.sin();
+
+    (x, y)
+}
+
+/// One algorithm on one distribution.
+#[derive(Debug, Clone)]
+pub struct Run {
+    pub algorithm:    &'static str,
+    pub distribution: Distribution,
+    pub seconds:      f64,
+    pub vertices:     usize,
+    /// Whether the hull is the same as the first algorithm's on these points.
+    pub agrees:       bool,
+}
+
+fn run<A: HullAlgorithm>(
+    distribution: Distribution,
+    points: &[(f64, f64)],
+    expected: &mut Option<Vec<usize>>,
+) -> Run {
+    let now = Instant::now();
+    let hull = A::par_hull(points, Collinear::Drop);
+    let seconds = now.elapsed().as_secs_f64();
+
+    let agrees = &hull == expected.get_or_insert_with(|| hull.clone());
+    Run { algorithm: A::NAME, distribution, seconds, vertices: hull.len(), agrees }
+}
+
+/// Every algorithm on the same `n` points of each distribution, each run telling whether it
+/// returned the same hull as the Graham scan.
+pub fn compare(n: usize) -> Vec<Run> {
+    let mut runs = vec![];
+    for &distribution in Distribution::ALL.iter() {
+        let points = distribution.points(n);
+        let mut expected = None;
+        runs.push(run::<GrahamScan>(distribution, &points, &mut expected));
+        runs.push(run::<MonotoneChain>(distribution, &points, &mut expected));
+        runs.push(run::<QuickHull>(distribution, &points, &mut expected));
+        runs.push(run::<Chan>(distribution, &points, &mut expected));
+        runs.push(run::<ParallelChain>(distribution, &points, &mut expected));
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_everything() {
+        let runs = compare(1000);
+        assert_eq!(runs.len(), 15);
+        for run in runs.iter() {
+            assert!(run.agrees, "{:?}", run);
+            match run.distribution {
+                Distribution::Circle => assert!(run.vertices > 900, "{:?}", run),
+                _ => assert!(run.vertices < 100, "{:?}", run),
+            }
+        }
+    }
+}
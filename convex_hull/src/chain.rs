@@ -0,0 +1,78 @@
+//! Andrew's monotone chain: the points sorted by x, then the lower hull left to right and the
+//! upper hull back, each a single pass dropping every point that doesn't turn left.
+
+use std::cmp::Ordering;
+
+use crate::{
+    algorithm::{finish, HullAlgorithm},
+    hull::{same, Collinear, Entry},
+    point::{Coordinate, Coordinates},
+    predicates::{orientation, Orientation},
+};
+
+pub struct MonotoneChain;
+
+impl HullAlgorithm for MonotoneChain {
+    const NAME: &'static str = "monotone chain";
+
+    fn hull<P: Coordinates>(points: &[P], collinear: Collinear) -> Vec<usize> {
+        let entries = points.iter().enumerate().map(|(i, p)| ([p.x(), p.y()], i)).collect();
+        finish(points, vertices(entries), collinear)
+    }
+}
+
+// leftmost first, the lowest of the leftmost
+pub(crate) fn leftmost<C: Coordinate>(a: &[C; 2], b: &[C; 2]) -> Ordering {
+    a[0].total_cmp(&b[0]).then(a[1].total_cmp(&b[1]))
+}
+
+/// Vertices of the hull from the leftmost, as [`finish`] takes them.
+pub(crate) fn vertices<C: Coordinate>(mut entries: Vec<Entry<C>>) -> Vec<Entry<C>> {
+    let mut hull = Vec::new();
+    extend_hull(&mut entries, &mut hull);
+    hull
+}
+
+/// Same as [`vertices`], appended to `hull`, sorting `entries` in place.
+pub(crate) fn extend_hull<C: Coordinate>(entries: &mut [Entry<C>], hull: &mut Vec<Entry<C>>) {
+    entries.sort_unstable_by(|(a, i), (b, j)| leftmost(a, b).then(i.cmp(j)));
+    // the first of duplicates moved to the front
+    let mut distinct = 0;
+    for i in 0..entries.len() {
+        if distinct == 0 || !same(&entries[distinct - 1].0, &entries[i].0) {
+            entries[distinct] = entries[i];
+            distinct += 1;
+        }
+    }
+    let entries = &entries[..distinct];
+    if entries.len() < 2 {
+        hull.extend_from_slice(entries);
+        return;
+    }
+
+    // the last point of each half starts the other
+    hull.reserve(entries.len() + 1);
+    extend_chain(hull, entries.iter(), Orientation::Left);
+    hull.pop();
+    extend_chain(hull, entries.iter().rev(), Orientation::Left);
+    hull.pop();
+}
+
+/// Appends the chain through `entries` turning `turn` at every point to `hull`, `entries` sorted
+/// as they come along it.
+pub(crate) fn extend_chain<'a, C: Coordinate + 'a>(
+    hull: &mut Vec<Entry<C>>,
+    entries: impl Iterator<Item = &'a Entry<C>>,
+    turn: Orientation,
+) {
+    let start = hull.len();
+    for entry in entries {
+        while let [.., (a, _), (b, _)] = hull[start..] {
+            if orientation(&a, &b, &entry.0) == turn {
+                break;
+            }
+            hull.pop();
+        }
+        hull.push(*entry);
+    }
+}
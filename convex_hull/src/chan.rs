@@ -0,0 +1,101 @@
+//! Chan's algorithm: the points split into groups of `m`, each group's hull built with the
+//! monotone chain, then a gift wrapping over the groups, where each step asks every group for
+//! its tangent in `O(log m)`. The wrapping gives up after `m` steps, and `m` is squared until
+//! it doesn't, which makes `O(n log h)` in all.
+
+use std::cmp::Ordering;
+
+use crate::{
+    algorithm::{finish, HullAlgorithm},
+    chain,
+    convex::{along, locate, tangent, Location},
+    hull::{lowest, Collinear, Entry},
+    point::{Coordinate, Coordinates},
+    predicates::{orientation, Orientation},
+};
+
+pub struct Chan;
+
+impl HullAlgorithm for Chan {
+    const NAME: &'static str = "Chan";
+
+    fn hull<P: Coordinates>(points: &[P], collinear: Collinear) -> Vec<usize> {
+        let mut entries: Vec<Entry<P::Coordinate>> =
+            points.iter().enumerate().map(|(i, p)| ([p.x(), p.y()], i)).collect();
+        let start = match entries.iter().min_by(|(a, i), (b, j)| lowest(a, b).then(i.cmp(j))) {
+            Some(&start) => start,
+            None => return vec![],
+        };
+
+        for t in 1.. {
+            let m = if t < 6 { (1usize << (1 << t)).min(entries.len()) } else { entries.len() };
+            let groups = Groups::new(&mut entries, m);
+            if let Some(vertices) = wrap(&groups, start, m) {
+                return finish(points, vertices, collinear);
+            }
+        }
+        unreachable!()
+    }
+}
+
+// the hulls of the groups one after the other
+struct Groups<C> {
+    vertices: Vec<[C; 2]>,
+    indices:  Vec<usize>,
+    // where each hull starts, and where the last one ends
+    bounds:   Vec<usize>,
+}
+
+impl<C: Coordinate> Groups<C> {
+    fn new(entries: &mut [Entry<C>], m: usize) -> Self {
+        let mut hull = Vec::new();
+        let mut groups = Self { vertices: vec![], indices: vec![], bounds: vec![0] };
+        for group in entries.chunks_mut(m) {
+            hull.clear();
+            chain::extend_hull(group, &mut hull);
+            groups.vertices.extend(hull.iter().map(|&(p, _)| p));
+            groups.indices.extend(hull.iter().map(|&(_, i)| i));
+            groups.bounds.push(groups.vertices.len());
+        }
+        groups
+    }
+
+    /// Each group's point the hull goes to after `p`, a vertex of the whole hull.
+    fn next<'a>(&'a self, p: &'a [C; 2]) -> impl Iterator<Item = Entry<C>> + 'a {
+        self.bounds.windows(2).filter_map(move |bounds| {
+            let (v, indices) = (&self.vertices[bounds[0]..bounds[1]], &self.indices[bounds[0]..]);
+            let k = match locate(v, p) {
+                Location::Vertex(_) if v.len() == 1 => return None,
+                Location::Vertex(k) => (k + 1) % v.len(),
+                _ => tangent(v, p),
+            };
+            Some((v[k], indices[k]))
+        })
+    }
+}
+
+// a comes first going round p, being more clockwise, or farther, or the first of duplicates
+fn before<C: Coordinate>(p: &[C; 2], (a, i): &Entry<C>, (b, j): &Entry<C>) -> bool {
+    match orientation(p, a, b) {
+        Orientation::Left => true,
+        Orientation::Right => false,
+        Orientation::Collinear => along(p, a, b).then(j.cmp(i)) == Ordering::Greater,
+    }
+}
+
+/// Vertices of the hull from `start`, unless there are more than `m`.
+fn wrap<C: Coordinate>(groups: &Groups<C>, start: Entry<C>, m: usize) -> Option<Vec<Entry<C>>> {
+    let mut hull = vec![start];
+    while hull.len() <= m {
+        let p = hull[hull.len() - 1].0;
+        let next = groups.next(&p).fold(None, |best, q| match best {
+            Some(best) if !before(&p, &q, &best) => Some(best),
+            _ => Some(q),
+        });
+        match next {
+            Some(q) if q.0 != start.0 => hull.push(q),
+            _ => return Some(hull),
+        }
+    }
+    None
+}
@@ -0,0 +1,205 @@
+//! Queries on a hull once it's built: where a point is relative to it, and which vertex a point
+//! outside sees as the most clockwise. Both are binary searches, `O(log h)`.
+
+use std::cmp::Ordering;
+
+use crate::{
+    point::{Coordinate, Coordinates},
+    predicates::{orientation, Orientation},
+};
+
+/// Where a point is relative to a convex polygon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Location {
+    Vertex(usize),
+    /// Strictly between vertex `k` and the next one.
+    Edge(usize),
+    Inside,
+    Outside,
+}
+
+/// How far `a` and `b` are from `p`, all three on a ray from `p`.
+pub fn along<P: Coordinates>(p: &P, a: &P, b: &P) -> Ordering {
+    let (k, l) = if p.x() != a.x() { (p.x(), [a.x(), b.x()]) } else { (p.y(), [a.y(), b.y()]) };
+    match k.total_cmp(&l[0]) {
+        Ordering::Less => l[0].total_cmp(&l[1]),
+        _ => l[1].total_cmp(&l[0]),
+    }
+}
+
+/// Where `p` is relative to the polygon with vertices `v`, strictly convex and counter-clockwise.
+/// A single point and a segment are polygons too.
+pub fn locate<C: Coordinate, P: Coordinates<Coordinate = C>>(v: &[[C; 2]], p: &P) -> Location {
+    let p = [p.x(), p.y()];
+    let is = |k: usize| p == v[k];
+    let h = v.len();
+    match h {
+        0 => return Location::Outside,
+        1 if is(0) => return Location::Vertex(0),
+        1 => return Location::Outside,
+        _ if is(0) => return Location::Vertex(0),
+        _ => {}
+    }
+
+    if h == 2 {
+        let on = orientation(&v[0], &v[1], &p) == Orientation::Collinear
+            && along(&v[0], &p, &v[1]) == Ordering::Less
+            && along(&v[1], &p, &v[0]) == Ordering::Less;
+        return match () {
+            _ if is(1) => Location::Vertex(1),
+            _ if on => Location::Edge(0),
+            _ => Location::Outside,
+        };
+    }
+
+    // the fan around the first vertex, p has to be between its first and last rays
+    let first = orientation(&v[0], &v[1], &p);
+    let last = orientation(&v[0], &v[h - 1], &p);
+    if first == Orientation::Right || last == Orientation::Left {
+        return Location::Outside;
+    }
+
+    // the last ray p isn't right of, short of the last one
+    let (mut lo, mut hi) = (1, h - 1);
+    while hi - lo > 1 {
+        let mid = (lo + hi) / 2;
+        match orientation(&v[0], &v[mid], &p) {
+            Orientation::Right => hi = mid,
+            _ => lo = mid,
+        }
+    }
+
+    match orientation(&v[lo], &v[lo + 1], &p) {
+        Orientation::Right => Location::Outside,
+        Orientation::Collinear if is(lo) => Location::Vertex(lo),
+        Orientation::Collinear if is(lo + 1) => Location::Vertex(lo + 1),
+        Orientation::Collinear => Location::Edge(lo),
+        _ if first == Orientation::Collinear => Location::Edge(0),
+        _ if last == Orientation::Collinear => Location::Edge(h - 1),
+        _ => Location::Inside,
+    }
+}
+
+/// The vertex of the polygon `v` the edges seen from `p` start from: every vertex is left of the
+/// line from `p` to it, or on it and not farther. `p` has to be outside.
+pub fn tangent<C: Coordinate, P: Coordinates<Coordinate = C>>(v: &[[C; 2]], p: &P) -> usize {
+    let p = [p.x(), p.y()];
+    // a is more clockwise than b
+    let before = |a: usize, b: usize| {
+        let (a, b) = (&v[a], &v[b]);
+        match orientation(&p, a, b) {
+            Orientation::Left => true,
+            Orientation::Right => false,
+            Orientation::Collinear => along(&p, a, b) == Ordering::Greater,
+        }
+    };
+
+    let h = v.len();
+    if h <= 3 {
+        return (1..h).fold(0, |best, k| if before(k, best) { k } else { best });
+    }
+    if before(0, 1) && before(0, h - 1) {
+        return 0;
+    }
+
+    // seen from p, going round the polygon turns one way until the tangent and the other
+    // way after it. Starting off the first vertex, the tangent is where it stops going
+    // clockwise, and if it starts counter-clockwise, after coming back past the start
+    let up = before(0, 1);
+    let past = |k: usize| {
+        let (above, down) = (before(0, k), k + 1 < h && before(k + 1, k));
+        if up {
+            above || down
+        } else {
+            !above && down
+        }
+    };
+    let (mut lo, mut hi) = (1, h - 1);
+    while lo < hi {
+        let mid = (lo + hi) / 2;
+        if past(mid) {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // an octagon with a flat top, every coordinate even
+    const OCTAGON: [[i64; 2]; 8] = [[2, 0], [6, 0], [8, 2], [8, 6], [6, 8], [2, 8], [0, 6], [0, 2]];
+
+    #[test]
+    fn locate_on_a_grid() {
+        let v = &OCTAGON;
+        let edge = |k: usize| (&v[k], &v[(k + 1) % v.len()]);
+        for x in -2..11 {
+            for y in -2..11 {
+                let p = [x, y];
+                let expected = if let Some(k) = v.iter().position(|&v| v == p) {
+                    Location::Vertex(k)
+                } else if let Some(k) = (0..v.len()).find(|&k| {
+                    let (a, b) = edge(k);
+                    orientation(a, b, &p) == Orientation::Collinear
+                        && along(a, &p, b) == Ordering::Less
+                        && along(b, &p, a) == Ordering::Less
+                }) {
+                    Location::Edge(k)
+                } else if (0..v.len()).all(|k| {
+                    let (a, b) = edge(k);
+                    orientation(a, b, &p) == Orientation::Left
+                }) {
+                    Location::Inside
+                } else {
+                    Location::Outside
+                };
+                assert_eq!(locate(v, &p), expected, "{:?}", p);
+            }
+        }
+    }
+
+    #[test]
+    fn small_polygons() {
+        let segment = [[0, 0], [4, 2]];
+        assert_eq!(locate(&segment, &[2, 1]), Location::Edge(0));
+        assert_eq!(locate(&segment, &[4, 2]), Location::Vertex(1));
+        assert_eq!(locate(&segment, &[6, 3]), Location::Outside);
+        assert_eq!(locate(&segment, &[1, 1]), Location::Outside);
+
+        let point = [[1, 1]];
+        assert_eq!(locate(&point, &[1, 1]), Location::Vertex(0));
+        assert_eq!(locate(&point, &[1, 2]), Location::Outside);
+    }
+
+    #[test]
+    fn tangent_on_a_grid() {
+        // every rotation, so each vertex gets to be the first
+        for shift in 0..8 {
+            let mut v = OCTAGON;
+            v.rotate_left(shift);
+
+            for x in -6..15 {
+                for y in -6..15 {
+                    let p = [x, y];
+                    if locate(&v, &p) != Location::Outside {
+                        continue;
+                    }
+                    let k = tangent(&v, &p);
+                    for q in v.iter() {
+                        match orientation(&p, &v[k], q) {
+                            Orientation::Left => {}
+                            Orientation::Collinear => {
+                                assert_ne!(along(&p, &v[k], q), Ordering::Less, "{:?}", p)
+                            }
+                            Orientation::Right => panic!("{:?} sees {:?} first", p, q),
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,543 @@
+//! A hull kept up to date as points come and go, after Overmars and van Leeuwen. The points sit
+//! in a treap ordered by x, and every node only keeps where the lower and upper chains of its
+//! subtree join those of its children: a start of the left chain, the node's own point maybe,
+//! then an end of the right chain. Any vertex of a chain is then found going down the tree.
+//!
+//! The vertices around where a node's chain joins its children's are kept too, so a search
+//! along a chain for where some turn changes goes down the tree once, `O(log n)`. Joining two
+//! chains is such a search for the bridge, each step one more for the tangent from a vertex of
+//! the left chain, so a node costs `O(log^2 n)` to update and an edit, going over a path of the
+//! treap, `O(log^3 n)` expected. Whether a point is inside is a binary search along each chain,
+//! `O(log^2 n)`, and the hull comes out in `O(h log n)`, plus locating every point when the ones
+//! on the edges are kept.
+
+use std::{cmp::Ordering, collections::BTreeSet};
+
+use crate::{
+    algorithm::finish_entries,
+    chain::leftmost,
+    hull::{Collinear, Entry},
+    point::{Coordinate, Coordinates},
+    predicates::{orientation, Orientation},
+};
+
+// the chains, and which way each turns going right
+const LOWER: usize = 0;
+const UPPER: usize = 1;
+const TURNS: [Orientation; 2] = [Orientation::Left, Orientation::Right];
+
+// how the chain of a subtree is made from its children's
+#[derive(Debug, Clone, Copy)]
+struct Join<C> {
+    // vertices of the left chain before the node's point
+    before: usize,
+    // vertices of those and the point that stay
+    kept:   usize,
+    // where the right chain is taken up
+    from:   usize,
+    len:    usize,
+    // the last vertex of the left chain before the point, if any
+    last:   [C; 2],
+    // the last vertex kept and the first one of the right chain, if there's a right chain
+    bridge: [[C; 2]; 2],
+}
+
+impl<C: Coordinate> Join<C> {
+    fn single(p: [C; 2]) -> Self {
+        Self { before: 0, kept: 1, from: 0, len: 1, last: p, bridge: [p, p] }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Node<C> {
+    point:    [C; 2],
+    // points with these coordinates
+    ids:      BTreeSet<usize>,
+    priority: u64,
+    left:     Option<usize>,
+    right:    Option<usize>,
+    chains:   [Join<C>; 2],
+}
+
+/// A set of points under insertion and removal, along with its hull.
+#[derive(Debug, Clone)]
+pub struct DynamicHull<C> {
+    nodes:  Vec<Node<C>>,
+    // nodes to reuse
+    free:   Vec<usize>,
+    root:   Option<usize>,
+    // by id, the removed ones taken
+    points: Vec<Option<[C; 2]>>,
+    len:    usize,
+}
+
+impl<C: Coordinate> Default for DynamicHull<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// how many of `0..n` `holds` holds for, it holding for a start of them
+fn prefix(n: usize, holds: impl Fn(usize) -> bool) -> usize {
+    let (mut lo, mut hi) = (0, n);
+    while lo < hi {
+        let mid = (lo + hi) / 2;
+        if holds(mid) {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
+
+impl<C: Coordinate> DynamicHull<C> {
+    pub fn new() -> Self {
+        Self { nodes: vec![], free: vec![], root: None, points: vec![], len: 0 }
+    }
+
+    /// Points in the set.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Adds `p`, returning its id. Ids are handed out from 0 up and never reused, so the hull
+    /// is the one of the points still there, in the order they were added. Each id ever handed
+    /// out takes a slot, removed or not, until [`compact`](Self::compact) renumbers them.
+    pub fn insert<P: Coordinates<Coordinate = C>>(&mut self, p: &P) -> usize {
+        let point = [p.x(), p.y()];
+        let id = self.points.len();
+        self.points.push(Some(point));
+        self.len += 1;
+        if let Some(v) = self.find(&point) {
+            self.nodes[v].ids.insert(id);
+            return id;
+        }
+
+        let node = Node {
+            point,
+            ids: Some(id).into_iter().collect(),
+            priority: rand::random(),
+            left: None,
+            right: None,
+            chains: [Join::single(point); 2],
+        };
+        let v = match self.free.pop() {
+            Some(v) => {
+                self.nodes[v] = node;
+                v
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        };
+        self.pull(v);
+        self.root = Some(self.add(self.root, v));
+        id
+    }
+
+    /// Removes the point with id `id`, false if there's none.
+    pub fn remove(&mut self, id: usize) -> bool {
+        let point = match self.points.get_mut(id).and_then(Option::take) {
+            Some(point) => point,
+            None => return false,
+        };
+        self.len -= 1;
+        let v = self.find(&point).expect("every point has a node");
+        self.nodes[v].ids.remove(&id);
+        if self.nodes[v].ids.is_empty() {
+            self.root = self.take(self.root.unwrap(), &point);
+            self.free.push(v);
+        }
+        true
+    }
+
+    /// Same as [`convex_hull_with`](crate::convex_hull_with) over the points still there, with
+    /// their ids.
+    pub fn hull(&self, collinear: Collinear) -> Vec<usize> {
+        let root = match self.root {
+            Some(root) => root,
+            None => return vec![],
+        };
+        let entry = |c: usize, k: usize| {
+            let node = &self.nodes[self.vertex(root, c, k)];
+            (node.point, *node.ids.iter().next().unwrap())
+        };
+        let (lower, upper) =
+            (self.nodes[root].chains[LOWER].len, self.nodes[root].chains[UPPER].len);
+        let mut vertices: Vec<Entry<C>> = (0..lower).map(|k| entry(LOWER, k)).collect();
+        // the upper chain comes back, without the ends the lower one has
+        vertices.extend((1..upper.saturating_sub(1)).rev().map(|k| entry(UPPER, k)));
+
+        // a node's first id is the first of its duplicates, the nodes are only walked when the
+        // points on the edges are kept
+        let points = self.live().map(|v| {
+            let node = &self.nodes[v];
+            (node.point, *node.ids.iter().next().unwrap())
+        });
+        finish_entries(points, vertices, collinear)
+    }
+
+    /// Gives the points still there new ids from 0 up, in the order they were added, returning
+    /// the new id of each old one, `None` for the removed ones. Removed points take no room
+    /// after that.
+    pub fn compact(&mut self) -> Vec<Option<usize>> {
+        let mut ids = vec![None; self.points.len()];
+        let mut len = 0;
+        for (id, p) in self.points.iter().enumerate() {
+            if p.is_some() {
+                ids[id] = Some(len);
+                len += 1;
+            }
+        }
+        self.points.retain(Option::is_some);
+        self.points.shrink_to_fit();
+        for v in self.live().collect::<Vec<_>>() {
+            let node = &mut self.nodes[v];
+            node.ids = node.ids.iter().map(|&i| ids[i].unwrap()).collect();
+        }
+        ids
+    }
+
+    /// Whether `p` is inside the hull or on it.
+    pub fn contains<P: Coordinates<Coordinate = C>>(&self, p: &P) -> bool {
+        let p = [p.x(), p.y()];
+        let root = match self.root {
+            Some(root) => root,
+            None => return false,
+        };
+
+        // above the lower chain and below the upper one, at the edge over or under p
+        (0..2).all(|c| {
+            let n = self.nodes[root].chains[c].len;
+            let at = |k| self.point(root, c, k);
+            match prefix(n, |k| leftmost(&at(k), &p) != Ordering::Greater) {
+                0 => false,
+                k if k == n => at(n - 1) == p,
+                k => orientation(&at(k - 1), &at(k), &p) != TURNS[1 - c],
+            }
+        })
+    }
+
+    // nodes of the points still there, walked as they're asked for
+    fn live(&self) -> impl Iterator<Item = usize> + '_ {
+        let mut stack: Vec<usize> = self.root.into_iter().collect();
+        std::iter::from_fn(move || {
+            let v = stack.pop()?;
+            stack.extend(self.nodes[v].left.into_iter().chain(self.nodes[v].right));
+            Some(v)
+        })
+    }
+
+    fn find(&self, point: &[C; 2]) -> Option<usize> {
+        let mut v = self.root;
+        while let Some(node) = v.map(|v| &self.nodes[v]) {
+            v = match leftmost(point, &node.point) {
+                Ordering::Less => node.left,
+                Ordering::Equal => return v,
+                Ordering::Greater => node.right,
+            };
+        }
+        None
+    }
+
+    /// Node of vertex `k` of chain `c` of the subtree at `v`.
+    fn vertex(&self, mut v: usize, c: usize, mut k: usize) -> usize {
+        loop {
+            let node = &self.nodes[v];
+            let join = node.chains[c];
+            if k >= join.kept {
+                k = join.from + k - join.kept;
+                v = node.right.unwrap();
+            } else if k < join.before {
+                v = node.left.unwrap();
+            } else {
+                return v;
+            }
+        }
+    }
+
+    fn point(&self, v: usize, c: usize, k: usize) -> [C; 2] {
+        self.nodes[self.vertex(v, c, k)].point
+    }
+
+    // the chains of `v` from those of its children
+    fn pull(&mut self, v: usize) {
+        let chains = [self.join(v, LOWER), self.join(v, UPPER)];
+        self.nodes[v].chains = chains;
+    }
+
+    /// The first edge from `lo` up to `hi` of chain `c` of the subtree at `v` that `holds`
+    /// doesn't hold for, or `hi`, `holds` holding for a start of them.
+    fn search(
+        &self,
+        v: usize,
+        c: usize,
+        lo: usize,
+        hi: usize,
+        holds: &impl Fn(&[C; 2], &[C; 2]) -> bool,
+    ) -> usize {
+        if lo >= hi {
+            return lo;
+        }
+        let node = &self.nodes[v];
+        let Join { before, kept, from, last, bridge, .. } = node.chains[c];
+        // edge k of the right chain is edge k - from + kept of this one
+        let right = |lo: usize, hi: usize| {
+            let right = node.right.unwrap();
+            self.search(right, c, lo + from - kept, hi + from - kept, holds) + kept - from
+        };
+
+        // the bridge is edge kept - 1, before it come the left chain and the point
+        if lo >= kept {
+            return right(lo, hi);
+        }
+        let mut hi = hi;
+        if hi >= kept {
+            if !holds(&bridge[0], &bridge[1]) {
+                hi = kept - 1;
+            } else if hi == kept {
+                return hi;
+            } else {
+                return right(kept, hi);
+            }
+        }
+
+        // then edge before - 1 from the left chain to the point, if the point stays
+        if kept == before + 1 && hi == before && lo < hi {
+            if holds(&last, &node.point) {
+                return hi;
+            }
+            hi -= 1;
+        }
+        match node.left {
+            Some(left) => self.search(left, c, lo, hi, holds),
+            None => lo,
+        }
+    }
+
+    fn join(&self, v: usize, c: usize) -> Join<C> {
+        let Node { point: p, left, right, .. } = self.nodes[v];
+        let turn = TURNS[c];
+        let len = |v: Option<usize>| v.map_or(0, |v| self.nodes[v].chains[c].len);
+        let search = |v: Option<usize>, hi: usize, holds: &dyn Fn(&[C; 2], &[C; 2]) -> bool| {
+            v.map_or(0, |v| self.search(v, c, 0, hi, &holds))
+        };
+
+        // the left chain as far as it turns the right way towards p
+        let before = match left {
+            Some(_) => search(left, len(left) - 1, &|a, b| orientation(a, b, &p) == turn) + 1,
+            None => 0,
+        };
+        let last = if before > 0 { self.point(left.unwrap(), c, before - 1) } else { p };
+        let right = match right {
+            Some(right) => right,
+            None => {
+                let len = before + 1;
+                return Join { before, kept: len, from: 0, len, last, bridge: [p, p] };
+            }
+        };
+
+        // then the bridge to the right chain: as far as the left chain and the point turn the
+        // right way towards their tangent, the tangent being where the right chain starts
+        // turning the right way
+        let n = len(Some(right));
+        let tangent = |q: &[C; 2]| {
+            let j = self.search(right, c, 0, n - 1, &|a, b| orientation(q, a, b) != turn);
+            (j, self.point(right, c, j))
+        };
+        let bridges = |a: &[C; 2], b: &[C; 2]| orientation(a, b, &tangent(b).1) == turn;
+        let mut kept = search(left, before.saturating_sub(1), &bridges) + 1;
+        if kept == before && bridges(&last, &p) {
+            kept += 1;
+        }
+
+        let end = if kept > before { p } else { self.point(left.unwrap(), c, kept - 1) };
+        let (from, start) = tangent(&end);
+        Join { before, kept, from, len: kept + n - from, last, bridge: [end, start] }
+    }
+
+    // the subtree at `v` with the node `new` added, rotated up as far as its priority goes
+    fn add(&mut self, v: Option<usize>, new: usize) -> usize {
+        let v = match v {
+            Some(v) => v,
+            None => return new,
+        };
+
+        let child = if leftmost(&self.nodes[new].point, &self.nodes[v].point) == Ordering::Less {
+            let left = self.add(self.nodes[v].left, new);
+            self.nodes[v].left = Some(left);
+            if self.nodes[left].priority > self.nodes[v].priority {
+                self.nodes[v].left = self.nodes[left].right;
+                self.nodes[left].right = Some(v);
+            }
+            left
+        } else {
+            let right = self.add(self.nodes[v].right, new);
+            self.nodes[v].right = Some(right);
+            if self.nodes[right].priority > self.nodes[v].priority {
+                self.nodes[v].right = self.nodes[right].left;
+                self.nodes[right].left = Some(v);
+            }
+            right
+        };
+
+        self.pull(v);
+        if self.nodes[child].priority > self.nodes[v].priority {
+            self.pull(child);
+            child
+        } else {
+            v
+        }
+    }
+
+    // the subtree at `v` without the node at `point`
+    fn take(&mut self, v: usize, point: &[C; 2]) -> Option<usize> {
+        let Node { left, right, .. } = self.nodes[v];
+        match leftmost(point, &self.nodes[v].point) {
+            Ordering::Less => self.nodes[v].left = self.take(left.unwrap(), point),
+            Ordering::Equal => return self.merge(left, right),
+            Ordering::Greater => self.nodes[v].right = self.take(right.unwrap(), point),
+        }
+        self.pull(v);
+        Some(v)
+    }
+
+    // the subtrees at `a` and `b`, every point of `a` before those of `b`, as one
+    fn merge(&mut self, a: Option<usize>, b: Option<usize>) -> Option<usize> {
+        let (a, b) = match (a, b) {
+            (None, v) | (v, None) => return v,
+            (Some(a), Some(b)) => (a, b),
+        };
+
+        if self.nodes[a].priority > self.nodes[b].priority {
+            let right = self.merge(self.nodes[a].right, Some(b));
+            self.nodes[a].right = right;
+            self.pull(a);
+            Some(a)
+        } else {
+            let left = self.merge(Some(a), self.nodes[b].left);
+            self.nodes[b].left = left;
+            self.pull(b);
+            Some(b)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        convex::{locate, Location},
+        hull::convex_hull_with,
+        testing::lcg,
+    };
+
+    // the batch hull of the points still there, with their ids
+    fn batch<C: Coordinate>(points: &[Option<[C; 2]>], collinear: Collinear) -> Vec<usize> {
+        let (live, ids): (Vec<[C; 2]>, Vec<usize>) =
+            points.iter().enumerate().filter_map(|(i, p)| p.map(|p| (p, i))).unzip();
+        convex_hull_with(&live, collinear).into_iter().map(|i| ids[i]).collect()
+    }
+
+    fn check<C: Coordinate>(hull: &DynamicHull<C>, queries: &[[C; 2]]) {
+        for &collinear in [Collinear::Drop, Collinear::Keep].iter() {
+            assert_eq!(hull.hull(collinear), batch(&hull.points, collinear), "{:?}", collinear);
+        }
+
+        let vertices: Vec<[C; 2]> =
+            hull.hull(Collinear::Drop).iter().map(|&i| hull.points[i].unwrap()).collect();
+        for q in queries {
+            assert_eq!(hull.contains(q), locate(&vertices, q) != Location::Outside);
+        }
+    }
+
+    #[test]
+    fn edits_on_a_grid() {
+        // few enough coordinates for duplicates and points on the edges
+        for seed in 0..30 {
+            let (mut next, mut pick) = (lcg(seed), lcg(seed + 1000));
+            let size = [3, 6, 20][seed as usize % 3];
+            let mut point = || [(next() % size) as i64, (next() % size) as i64];
+            let queries: Vec<[i64; 2]> = (0..40).map(|_| point()).collect();
+
+            let mut hull = DynamicHull::new();
+            for step in 0..150 {
+                if step % 3 == 2 {
+                    let id = pick() as usize % hull.points.len();
+                    let there = hull.points[id].is_some();
+                    assert_eq!(hull.remove(id), there);
+                } else {
+                    hull.insert(&point());
+                }
+                check(&hull, &queries);
+            }
+        }
+    }
+
+    #[test]
+    fn grows_and_empties() {
+        let mut next = lcg(7);
+        let mut point =
+            || [next() as f64 / (1u64 << 31) as f64, next() as f64 / (1u64 << 31) as f64];
+        let queries: Vec<[f64; 2]> = (0..100).map(|_| point()).collect();
+
+        let mut hull = DynamicHull::new();
+        for _ in 0..1000 {
+            hull.insert(&point());
+        }
+        check(&hull, &queries);
+        assert_eq!(hull.len(), 1000);
+
+        for id in (0..1000).rev().step_by(7) {
+            assert!(hull.remove(id));
+            assert!(!hull.remove(id));
+        }
+        check(&hull, &queries);
+        for id in 0..1000 {
+            hull.remove(id);
+        }
+        assert!(hull.is_empty());
+        assert_eq!(hull.hull(Collinear::Drop), []);
+        assert!(!hull.contains(&queries[0]));
+    }
+
+    #[test]
+    fn compacts() {
+        let mut next = lcg(11);
+        let mut point = || [(next() % 10) as i64, (next() % 10) as i64];
+        let queries: Vec<[i64; 2]> = (0..40).map(|_| point()).collect();
+
+        let mut hull = DynamicHull::new();
+        for _ in 0..300 {
+            hull.insert(&point());
+        }
+        for id in (0..300).filter(|id| id % 3 != 0) {
+            hull.remove(id);
+        }
+        let before: Vec<Vec<usize>> =
+            [Collinear::Drop, Collinear::Keep].iter().map(|&c| hull.hull(c)).collect();
+
+        let ids = hull.compact();
+        assert_eq!(hull.points.len(), 100);
+        for (id, new) in ids.iter().enumerate() {
+            assert_eq!(*new, if id % 3 == 0 { Some(id / 3) } else { None });
+        }
+        for (&collinear, before) in [Collinear::Drop, Collinear::Keep].iter().zip(before) {
+            let renumbered: Vec<usize> = before.iter().map(|&i| ids[i].unwrap()).collect();
+            assert_eq!(hull.hull(collinear), renumbered);
+        }
+        check(&hull, &queries);
+
+        // removing and adding again go on from the new ids
+        assert!(hull.remove(99));
+        assert!(!hull.remove(100));
+        assert_eq!(hull.insert(&[3, 3]), 100);
+        check(&hull, &queries);
+    }
+}
@@ -0,0 +1,285 @@
+//! Graham scan: the points sorted by angle around the lowest one, then a single pass that drops
+//! every point making a right turn.
+//!
+//! Both the sort and the scan only ask which way three points turn, which is answered exactly
+//! (see [`predicates`](crate::predicates)), so round-off can't make the sort inconsistent or let
+//! a point slightly inside the hull through.
+
+use std::cmp::Ordering;
+
+use crate::{
+    point::{Coordinate, Coordinates},
+    predicates::{orientation, Orientation},
+};
+
+/// What to do with points lying on an edge of the hull.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Collinear {
+    /// Only the corners.
+    #[default]
+    Drop,
+    /// Every point on the boundary.
+    Keep,
+}
+
+// coordinates of a point next to its index, so sorting doesn't jump around the points
+pub(crate) type Entry<C> = ([C; 2], usize);
+
+// lowest first, the leftmost of the lowest
+pub(crate) fn lowest<P: Coordinates>(a: &P, b: &P) -> Ordering {
+    a.y().total_cmp(&b.y()).then(a.x().total_cmp(&b.x()))
+}
+
+pub(crate) fn same<P: Coordinates>(a: &P, b: &P) -> bool {
+    a.x() == b.x() && a.y() == b.y()
+}
+
+/// Same as `convex_hull_with(points, Collinear::Drop)`.
+pub fn convex_hull<P: Coordinates>(points: &[P]) -> Vec<usize> {
+    convex_hull_with(points, Collinear::Drop)
+}
+
+/// Indices of the hull's vertices, counter-clockwise from the lowest point (the leftmost of them
+/// if there are several). Of duplicates only the first is returned. When all the points are on
+/// a line, they go from the lowest to the farthest one. Coordinates must not be NaN, and the
+/// result is exact for any that aren't, see [`predicates`](crate::predicates).
+pub fn convex_hull_with<P: Coordinates>(points: &[P], collinear: Collinear) -> Vec<usize> {
+    let origin = match (0..points.len()).min_by(|&a, &b| lowest(&points[a], &points[b])) {
+        Some(origin) => origin,
+        None => return vec![],
+    };
+    let o = &points[origin];
+
+    // every other point is above the origin or right of it, so the angles are in [0, pi) and
+    // a turn tells which of two comes first, points on the same ray are lower the closer
+    let center = [o.x(), o.y()];
+    let exact = |(a, i): &Entry<P::Coordinate>, (b, j): &Entry<P::Coordinate>| match orientation(
+        &center, a, b,
+    ) {
+        Orientation::Left => Ordering::Less,
+        Orientation::Right => Ordering::Greater,
+        Orientation::Collinear => lowest(a, b).then(i.cmp(j)),
+    };
+
+    // sorted by the rounded angle first, which is cheaper and only leaves near ties out of
+    // order, the exact sort then mostly walks over runs that are already sorted
+    let angle = |[x, y]: [P::Coordinate; 2]| {
+        (y.approx() - center[1].approx()).atan2(x.approx() - center[0].approx())
+    };
+    let mut order: Vec<(f64, Entry<P::Coordinate>)> = (0..points.len())
+        .filter(|&i| !same(&points[i], o))
+        .map(|i| {
+            let p = [points[i].x(), points[i].y()];
+            (angle(p), (p, i))
+        })
+        .collect();
+    order.sort_unstable_by(|(x, a), (y, b)| x.total_cmp(y).then_with(|| exact(a, b)));
+    let mut order: Vec<Entry<P::Coordinate>> = order.into_iter().map(|(_, x)| x).collect();
+    order.sort_by(exact);
+
+    // the first of duplicates stays
+    order.dedup_by(|(b, _), (a, _)| same(a, b));
+    let mut order: Vec<usize> = order.into_iter().map(|(_, i)| i).collect();
+
+    if collinear == Collinear::Keep {
+        // the last ray is walked back towards the origin
+        if let Some(&last) = order.last() {
+            let run = order
+                .iter()
+                .rev()
+                .take_while(|&&i| {
+                    orientation(o, &points[i], &points[last]) == Orientation::Collinear
+                })
+                .count();
+            if run < order.len() {
+                let start = order.len() - run;
+                order[start..].reverse();
+            }
+        }
+    }
+
+    let mut hull = vec![origin];
+    for i in order {
+        let p = &points[i];
+        while let [.., prelast, last] = hull[..] {
+            match orientation(&points[prelast], &points[last], p) {
+                Orientation::Left => break,
+                Orientation::Collinear if collinear == Collinear::Keep => break,
+                _ => hull.pop(),
+            };
+        }
+        hull.push(i);
+    }
+
+    hull
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::uniform;
+
+    // every point left of or on every edge
+    fn is_convex_hull(points: &[(f64, f64)], hull: &[usize]) -> bool {
+        (0..hull.len()).all(|k| {
+            let (a, b) = (&points[hull[k]], &points[hull[(k + 1) % hull.len()]]);
+            points.iter().all(|p| orientation(a, b, p) != Orientation::Right)
+        })
+    }
+
+    #[test]
+    fn square_with_inside_points() {
+        let points = [(0.5, 0.5), (1., 1.), (0., 1.), (0.2, 0.7), (1., 0.), (0., 0.), (0.9, 0.1)];
+        assert_eq!(convex_hull(&points), [5, 4, 1, 2]);
+    }
+
+    #[test]
+    fn starts_at_the_lowest_leftmost_point() {
+        let points = [[3., 0.], [4., 2.], [1., 0.], [0., 3.], [2., 4.]];
+        assert_eq!(convex_hull(&points), [2, 0, 1, 4, 3]);
+    }
+
+    #[test]
+    fn degenerate() {
+        let empty: [(f64, f64); 0] = [];
+        assert_eq!(convex_hull(&empty), []);
+        assert_eq!(convex_hull(&[(1., 1.)]), [0]);
+        assert_eq!(convex_hull(&[(1., 1.), (1., 1.), (1., 1.)]), [0]);
+
+        // duplicates of a vertex and points on the edges
+        let points = [(0., 0.), (2., 0.), (1., 0.), (2., 2.), (2., 0.), (0., 2.), (0., 1.)];
+        assert_eq!(convex_hull(&points), [0, 1, 3, 5]);
+        assert_eq!(convex_hull_with(&points, Collinear::Keep), [0, 2, 1, 3, 5, 6]);
+    }
+
+    #[test]
+    fn all_collinear() {
+        let points = [(2., 2.), (0., 0.), (1., 1.), (3., 3.), (1., 1.)];
+        assert_eq!(convex_hull(&points), [1, 3]);
+        assert_eq!(convex_hull_with(&points, Collinear::Keep), [1, 2, 0, 3]);
+
+        // vertical, where the tangent used to be infinite
+        let points = [(5., 3.), (5., -1.), (5., 7.)];
+        assert_eq!(convex_hull(&points), [1, 2]);
+        assert_eq!(convex_hull_with(&points, Collinear::Keep), [1, 0, 2]);
+    }
+
+    #[test]
+    fn vertical_neighbours() {
+        // several points straight above the origin and above each other
+        let points = [(0., 0.), (0., 2.), (0., 1.), (1., 1.), (1., 0.), (1., 3.), (0., 3.)];
+        assert_eq!(convex_hull(&points), [0, 4, 5, 6]);
+        assert_eq!(convex_hull_with(&points, Collinear::Keep), [0, 4, 3, 5, 6, 1, 2]);
+    }
+
+    #[test]
+    fn frame() {
+        // the Frame distribution: points on the sides of the unit square
+        let mut next = uniform(7);
+        let points: Vec<(f64, f64)> = (0..2000)
+            .map(|i| {
+                let (x, side) = (next(), (i / 2 % 2) as f64);
+                if i % 2 == 0 {
+                    (x, side)
+                } else {
+                    (side, x)
+                }
+            })
+            .collect();
+
+        let hull = convex_hull(&points);
+        assert!(is_convex_hull(&points, &hull));
+        // two ends on each side, unless a corner got picked
+        assert!(hull.len() <= 8);
+
+        let all = convex_hull_with(&points, Collinear::Keep);
+        assert!(is_convex_hull(&points, &all));
+        let mut sorted = all.clone();
+        sorted.sort_unstable();
+        sorted.dedup();
+        assert_eq!(sorted.len(), all.len());
+        // every point is on a side, only duplicates are left out
+        let mut distinct = points.clone();
+        distinct.sort_by(lowest);
+        distinct.dedup();
+        assert_eq!(all.len(), distinct.len());
+    }
+
+    // for every vertex of the hull the next one, counter-clockwise: b follows a when every
+    // point is left of a -> b or on the segment between them, O(n^3)
+    fn brute_force(points: &[(f64, f64)]) -> Vec<((f64, f64), (f64, f64))> {
+        let between = |a: f64, b: f64, x: f64| a.min(b) <= x && x <= a.max(b);
+        let mut edges = vec![];
+        for &a in points.iter() {
+            for &b in points.iter().filter(|&&b| b != a) {
+                let outside = points.iter().any(|p| match orientation(&a, &b, p) {
+                    Orientation::Left => false,
+                    Orientation::Right => true,
+                    Orientation::Collinear => !between(a.0, b.0, p.0) || !between(a.1, b.1, p.1),
+                });
+                if !outside {
+                    edges.push((a, b));
+                }
+            }
+        }
+        edges.sort_by(|x, y| lowest(&x.0, &y.0));
+        edges.dedup();
+        edges
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        for seed in 0..30 {
+            let mut next = uniform(seed);
+            // two seeds out of three on a small grid, for duplicates and points on the edges
+            let scale = [1., 4., 16.][seed as usize % 3];
+            let round = |x: f64| if scale == 1. { x } else { (x * scale).floor() };
+            let n = [5, 40, 150][seed as usize / 3 % 3];
+            let points: Vec<(f64, f64)> = (0..n).map(|_| (round(next()), round(next()))).collect();
+
+            let hull = convex_hull(&points);
+            let mut edges: Vec<_> = (0..hull.len())
+                .map(|k| (points[hull[k]], points[hull[(k + 1) % hull.len()]]))
+                .filter(|(a, b)| a != b)
+                .collect();
+            edges.sort_by(|x, y| lowest(&x.0, &y.0));
+            assert_eq!(edges, brute_force(&points), "{}", seed);
+
+            let lowest = (0..points.len()).min_by(|&a, &b| lowest(&points[a], &points[b]));
+            assert_eq!(hull.first().copied(), lowest);
+        }
+    }
+
+    #[test]
+    fn integers_past_2_53() {
+        // the last point is a unit above the diagonal, the one before a unit below it
+        let half = 1i64 << 61;
+        let points =
+            [[0, 0], [half * 2, 0], [half * 2, half * 2], [half + 1, half], [half, half + 1]];
+        assert_eq!(convex_hull(&points), [0, 1, 2, 4]);
+
+        // as floats, both land on the diagonal
+        let rounded: Vec<[f64; 2]> = points.iter().map(|p| [p[0] as f64, p[1] as f64]).collect();
+        assert_eq!(convex_hull(&rounded), [0, 1, 2]);
+    }
+
+    #[test]
+    fn integers_at_the_extremes() {
+        let (min, max) = (i64::MIN, i64::MAX);
+        let points = [(0, 0), (max, max), (min, max), (min, min), (max, min), (max, 0), (-1, min)];
+        assert_eq!(convex_hull(&points), [3, 4, 1, 2]);
+        assert_eq!(convex_hull_with(&points, Collinear::Keep), [3, 6, 4, 5, 1, 2]);
+    }
+
+    #[test]
+    fn integers_agree_with_floats() {
+        let mut next = uniform(99);
+        let points: Vec<(i64, i64)> =
+            (0..500).map(|_| ((next() * 64.) as i64 - 32, (next() * 64.) as i64 - 32)).collect();
+        let floats: Vec<(f64, f64)> = points.iter().map(|&(x, y)| (x as f64, y as f64)).collect();
+
+        for &collinear in [Collinear::Drop, Collinear::Keep].iter() {
+            assert_eq!(convex_hull_with(&points, collinear), convex_hull_with(&floats, collinear));
+        }
+    }
+}
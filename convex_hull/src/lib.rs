@@ -0,0 +1,22 @@
+pub mod algorithm;
+pub mod bench;
+pub mod chain;
+pub mod chan;
+pub mod convex;
+pub mod dynamic;
+pub mod hull;
+pub mod parallel;
+pub mod point;
+pub mod predicates;
+pub mod quickhull;
+#[cfg(test)]
+mod testing;
+
+pub use algorithm::{GrahamScan, HullAlgorithm};
+pub use chain::MonotoneChain;
+pub use chan::Chan;
+pub use dynamic::DynamicHull;
+pub use hull::{convex_hull, convex_hull_with, Collinear};
+pub use parallel::ParallelChain;
+pub use point::Coordinates;
+pub use quickhull::QuickHull;
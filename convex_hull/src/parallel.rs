@@ -0,0 +1,179 @@
+//! The monotone chain split over threads: the points are sorted in parallel, then the sorted
+//! run is halved until the pieces are small, the lower and upper chains of each half are built
+//! concurrently, and two neighbouring halves are joined at the bridge between their chains.
+//!
+//! The halves are split by x, so each chain of the union is a start of the left one's followed
+//! by an end of the right one's, and the bridge is found walking back along the left chain and
+//! on along the right one until both ends turn the right way. Duplicates are dropped and, when
+//! points on the edges are kept, every point is located on the hull in parallel too.
+//!
+//! Only points that can be shared between threads go through all that, with
+//! [`HullAlgorithm::par_hull`]. Others are left to the sequential monotone chain.
+
+use rayon::prelude::*;
+
+use crate::{
+    algorithm::{by_edge, finish_located, on_edge, HullAlgorithm},
+    chain::{extend_chain, leftmost, MonotoneChain},
+    hull::{same, Collinear, Entry},
+    point::{Coordinate, Coordinates},
+    predicates::{orientation, Orientation},
+};
+
+/// Points below which a piece is done on a single thread.
+const PIECE: usize = 1 << 14;
+
+pub struct ParallelChain;
+
+impl HullAlgorithm for ParallelChain {
+    const NAME: &'static str = "parallel chain";
+
+    fn hull<P: Coordinates>(points: &[P], collinear: Collinear) -> Vec<usize> {
+        MonotoneChain::hull(points, collinear)
+    }
+
+    fn par_hull<P>(points: &[P], collinear: Collinear) -> Vec<usize>
+    where
+        P: Coordinates + Sync,
+        P::Coordinate: Send + Sync,
+    {
+        let mut entries: Vec<Entry<P::Coordinate>> =
+            points.par_iter().enumerate().map(|(i, p)| ([p.x(), p.y()], i)).collect();
+        entries.par_sort_unstable_by(|(a, i), (b, j)| leftmost(a, b).then(i.cmp(j)));
+        // duplicates after the first dropped, in parallel
+        let entries: Vec<Entry<P::Coordinate>> = (0..entries.len())
+            .into_par_iter()
+            .filter(|&k| k == 0 || !same(&entries[k - 1].0, &entries[k].0))
+            .map(|k| entries[k])
+            .collect();
+
+        let (lower, upper) = chains(&entries, PIECE);
+        // the upper chain comes back, without the ends the lower one has
+        let mut vertices = lower;
+        vertices.extend(upper.iter().rev().skip(1).take(upper.len().saturating_sub(2)));
+        finish_located(vertices, collinear, |v| {
+            let mut located: Vec<_> = entries.par_iter().filter_map(|&x| on_edge(v, x)).collect();
+            located.par_sort_unstable_by(|a, b| by_edge(v, a, b));
+            located
+        })
+    }
+}
+
+// the lower chain turning left and the upper one turning right, both from the leftmost point
+type Chains<C> = (Vec<Entry<C>>, Vec<Entry<C>>);
+
+fn chains<C: Coordinate + Send + Sync>(entries: &[Entry<C>], piece: usize) -> Chains<C> {
+    if entries.len() <= piece {
+        let chain = |turn| {
+            let mut chain = vec![];
+            extend_chain(&mut chain, entries.iter(), turn);
+            chain
+        };
+        return (chain(Orientation::Left), chain(Orientation::Right));
+    }
+
+    let (left, right) = entries.split_at(entries.len() / 2);
+    let ((left_lower, left_upper), (right_lower, right_upper)) =
+        rayon::join(|| chains(left, piece), || chains(right, piece));
+    (
+        join(left_lower, &right_lower, Orientation::Left),
+        join(left_upper, &right_upper, Orientation::Right),
+    )
+}
+
+// the chain through the bridge from `left` to `right`, turning `turn` at every point
+fn join<C: Coordinate>(
+    mut left: Vec<Entry<C>>,
+    right: &[Entry<C>],
+    turn: Orientation,
+) -> Vec<Entry<C>> {
+    let (mut i, mut j) = (left.len() - 1, 0);
+    loop {
+        let (from, to) = (i, j);
+        while i > 0 && orientation(&left[i - 1].0, &left[i].0, &right[j].0) != turn {
+            i -= 1;
+        }
+        while j + 1 < right.len() && orientation(&left[i].0, &right[j].0, &right[j + 1].0) != turn {
+            j += 1;
+        }
+        if (i, j) == (from, to) {
+            break;
+        }
+    }
+
+    left.truncate(i + 1);
+    left.extend_from_slice(&right[j..]);
+    left
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{algorithm::finish, testing::lcg};
+
+    // deterministic coordinates in [0, size)
+    fn grid(seed: u64, n: usize, size: i64) -> Vec<Entry<i64>> {
+        let mut lcg = lcg(seed);
+        let mut next = move || lcg() as i64 % size;
+        let mut entries: Vec<Entry<i64>> = (0..n).map(|i| ([next(), next()], i)).collect();
+        entries.sort_unstable_by(|(a, i), (b, j)| leftmost(a, b).then(i.cmp(j)));
+        entries.dedup_by(|(b, _), (a, _)| same(a, b));
+        entries.iter().enumerate().map(|(i, &(p, _))| (p, i)).collect()
+    }
+
+    #[test]
+    fn small_pieces() {
+        // pieces of a few points, so almost everything is joined, with points on a line across
+        // the joins when the grid is small
+        for seed in 0..200 {
+            let (n, size) = [(10, 4), (50, 8), (300, 30), (1000, 1 << 40)][seed as usize % 4];
+            let entries = grid(seed, n, size);
+            let points: Vec<[i64; 2]> = entries.iter().map(|&(p, _)| p).collect();
+
+            for &piece in [1, 2, 3, 7].iter() {
+                let (lower, upper) = chains(&entries, piece);
+                let mut vertices = lower;
+                vertices.extend(upper.iter().rev().skip(1).take(upper.len().saturating_sub(2)));
+                let hull = finish(&points, vertices, Collinear::Keep);
+                assert_eq!(
+                    hull,
+                    MonotoneChain::hull(&points, Collinear::Keep),
+                    "{} {}",
+                    seed,
+                    piece
+                );
+            }
+        }
+    }
+
+    // a point that can't be shared between threads, counting how often it's read
+    struct Counted([i64; 2], std::cell::Cell<usize>);
+
+    impl Coordinates for Counted {
+        type Coordinate = i64;
+
+        fn x(&self) -> i64 {
+            self.1.set(self.1.get() + 1);
+            self.0[0]
+        }
+
+        fn y(&self) -> i64 {
+            self.0[1]
+        }
+    }
+
+    #[test]
+    fn points_not_shared() {
+        let entries = grid(3, 300, 30);
+        let points: Vec<Counted> =
+            entries.iter().map(|&(p, _)| Counted(p, Default::default())).collect();
+        let plain: Vec<[i64; 2]> = entries.iter().map(|&(p, _)| p).collect();
+
+        for &collinear in [Collinear::Drop, Collinear::Keep].iter() {
+            let expected = ParallelChain::par_hull(&plain, collinear);
+            assert_eq!(ParallelChain::hull(&points, collinear), expected);
+            assert_eq!(crate::convex_hull_with(&points, collinear), expected);
+        }
+        assert!(points.iter().all(|p| p.1.get() > 0));
+    }
+}
@@ -0,0 +1,88 @@
+//! What the hull needs to know about a point.
+
+use std::cmp::Ordering;
+
+use crate::predicates::{self, Orientation};
+
+/// Type of the coordinates, along with an exact orientation test for it.
+pub trait Coordinate: Copy + PartialEq {
+    /// Which way `d - c` points relative to `b - a`, exactly, see [`predicates::turn`].
+    fn turn(a: [Self; 2], b: [Self; 2], c: [Self; 2], d: [Self; 2]) -> Orientation;
+
+    fn total_cmp(&self, other: &Self) -> Ordering;
+
+    /// Nearest `f64`, only used to sort roughly before sorting exactly.
+    fn approx(self) -> f64;
+}
+
+impl Coordinate for f64 {
+    fn turn(a: [f64; 2], b: [f64; 2], c: [f64; 2], d: [f64; 2]) -> Orientation {
+        predicates::turn_f64(a, b, c, d)
+    }
+
+    fn total_cmp(&self, other: &f64) -> Ordering {
+        f64::total_cmp(self, other)
+    }
+
+    fn approx(self) -> f64 {
+        self
+    }
+}
+
+impl Coordinate for i64 {
+    fn turn(a: [i64; 2], b: [i64; 2], c: [i64; 2], d: [i64; 2]) -> Orientation {
+        predicates::turn_i64(a, b, c, d)
+    }
+
+    fn total_cmp(&self, other: &i64) -> Ordering {
+        self.cmp(other)
+    }
+
+    fn approx(self) -> f64 {
+        self as f64
+    }
+}
+
+/// A point in the plane, whatever else it carries.
+pub trait Coordinates {
+    type Coordinate: Coordinate;
+
+    fn x(&self) -> Self::Coordinate;
+    fn y(&self) -> Self::Coordinate;
+}
+
+impl<C: Coordinate> Coordinates for (C, C) {
+    type Coordinate = C;
+
+    fn x(&self) -> C {
+        self.0
+    }
+
+    fn y(&self) -> C {
+        self.1
+    }
+}
+
+impl<C: Coordinate> Coordinates for [C; 2] {
+    type Coordinate = C;
+
+    fn x(&self) -> C {
+        self[0]
+    }
+
+    fn y(&self) -> C {
+        self[1]
+    }
+}
+
+impl<P: Coordinates> Coordinates for &P {
+    type Coordinate = P::Coordinate;
+
+    fn x(&self) -> P::Coordinate {
+        (*self).x()
+    }
+
+    fn y(&self) -> P::Coordinate {
+        (*self).y()
+    }
+}
@@ -0,0 +1,206 @@
+//! Exact orientation of three points, and more generally of one direction relative to another:
+//! the sign of `(b - a) x (d - c)`.
+//!
+//! With `i64` coordinates the differences take 65 bits and their products 129, one more than
+//! an `i128` holds, so each product is worked out as a sign and a `u128` magnitude, and the two
+//! are compared rather than subtracted. That's exact for every input.
+//!
+//! With `f64` coordinates the determinant is first computed in plain floating point, and
+//! trusted whenever it's further from zero than the worst round-off can take it (Shewchuk's
+//! bound). Otherwise it's expanded into its eight products, each split exactly into a rounded
+//! product and its error with a fused multiply-add, and the sixteen terms are summed exactly
+//! into a nonoverlapping expansion whose largest component has the sign of the whole. That
+//! holds as long as nothing overflows or underflows, i.e. for coordinates roughly between
+//! 2^-500 and 2^500 in magnitude (or zero).
+
+use std::cmp::Ordering;
+
+use crate::point::{Coordinate, Coordinates};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Orientation {
+    /// Clockwise turn.
+    Right,
+    Collinear,
+    /// Counter-clockwise turn.
+    Left,
+}
+
+// (3 + 16 eps) eps, eps being half an ulp of 1
+const ERROR_BOUND: f64 = (3. + 16. * f64::EPSILON / 2.) * f64::EPSILON / 2.;
+
+/// Which way `a`, `b`, `c` turn: left if `c` is on the left of the line from `a` to `b`.
+pub fn orientation<P: Coordinates>(a: &P, b: &P, c: &P) -> Orientation {
+    turn(a, b, a, c)
+}
+
+/// Which way the direction from `c` to `d` points relative to the one from `a` to `b`: left if
+/// counter-clockwise of it.
+pub fn turn<P: Coordinates>(a: &P, b: &P, c: &P, d: &P) -> Orientation {
+    let xy = |p: &P| [p.x(), p.y()];
+    P::Coordinate::turn(xy(a), xy(b), xy(c), xy(d))
+}
+
+// sign and magnitude of x * y, ordered as the product is
+fn product(x: i128, y: i128) -> (Ordering, u128) {
+    let magnitude = x.unsigned_abs() * y.unsigned_abs();
+    let sign = (x.signum() * y.signum()).cmp(&0);
+    (sign, magnitude)
+}
+
+pub fn turn_i64(a: [i64; 2], b: [i64; 2], c: [i64; 2], d: [i64; 2]) -> Orientation {
+    let diff = |p: i64, q: i64| p as i128 - q as i128;
+    let left = product(diff(b[0], a[0]), diff(d[1], c[1]));
+    let right = product(diff(b[1], a[1]), diff(d[0], c[0]));
+
+    let order = match left.0.cmp(&right.0) {
+        Ordering::Equal if left.0 == Ordering::Less => right.1.cmp(&left.1),
+        Ordering::Equal => left.1.cmp(&right.1),
+        order => order,
+    };
+    match order {
+        Ordering::Greater => Orientation::Left,
+        Ordering::Less => Orientation::Right,
+        Ordering::Equal => Orientation::Collinear,
+    }
+}
+
+fn sign(x: f64) -> Orientation {
+    if x > 0. {
+        Orientation::Left
+    } else if x < 0. {
+        Orientation::Right
+    } else {
+        Orientation::Collinear
+    }
+}
+
+// a + b as the rounded sum and its error
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let sum = a + b;
+    let b_virtual = sum - a;
+    let a_virtual = sum - b_virtual;
+    (sum, (a - a_virtual) + (b - b_virtual))
+}
+
+fn two_product(a: f64, b: f64) -> (f64, f64) {
+    let product = a * b;
+    (product, a.mul_add(b, -product))
+}
+
+pub fn turn_f64(a: [f64; 2], b: [f64; 2], c: [f64; 2], d: [f64; 2]) -> Orientation {
+    let [ax, ay] = a;
+    let [bx, by] = b;
+    let [cx, cy] = c;
+    let [dx, dy] = d;
+    let left = (bx - ax) * (dy - cy);
+    let right = (by - ay) * (dx - cx);
+    let det = left - right;
+    if det.abs() >= ERROR_BOUND * (left.abs() + right.abs()) {
+        return sign(det);
+    }
+
+    // bx dy - bx cy - ax dy + ax cy - by dx + by cx + ay dx - ay cx
+    let products =
+        [(bx, dy), (-bx, cy), (-ax, dy), (ax, cy), (-by, dx), (by, cx), (ay, dx), (-ay, cx)];
+    // components from the smallest up, none overlapping the next
+    let mut expansion: Vec<f64> = Vec::with_capacity(16);
+    for &(x, y) in products.iter() {
+        let (product, error) = two_product(x, y);
+        for term in [error, product] {
+            let mut carry = term;
+            for component in expansion.iter_mut() {
+                let (sum, error) = two_sum(carry, *component);
+                *component = error;
+                carry = sum;
+            }
+            expansion.push(carry);
+        }
+    }
+
+    expansion.iter().rev().copied().find(|&x| x != 0.).map_or(Orientation::Collinear, sign)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cross(a: &(f64, f64), b: &(f64, f64), c: &(f64, f64)) -> f64 {
+        (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)
+    }
+
+    // exact sign for coordinates that are multiples of 2^-53 below 2^10
+    fn exact(a: (f64, f64), b: (f64, f64), c: (f64, f64)) -> Orientation {
+        let int = |x: f64| (x * (1u64 << 53) as f64) as i128;
+        let (ax, ay, bx, by, cx, cy) = (int(a.0), int(a.1), int(b.0), int(b.1), int(c.0), int(c.1));
+        let det = (bx - ax) * (cy - ay) - (by - ay) * (cx - ax);
+        sign(det.signum() as f64)
+    }
+
+    #[test]
+    fn near_collinear() {
+        // the usual example, a grid of tiny moves of one point near a line through the others
+        let (b, c) = ((12., 12.), (24., 24.));
+        let ulp = f64::EPSILON / 2.;
+        let mut wrong = 0;
+        for i in 0..64 {
+            for j in 0..64 {
+                let a = (0.5 + i as f64 * ulp, 0.5 + j as f64 * ulp);
+                let expected = exact(a, b, c);
+                assert_eq!(orientation(&a, &b, &c), expected, "{:?}", a);
+                assert_eq!(orientation(&b, &c, &a), expected, "{:?}", a);
+                if sign(cross(&a, &b, &c)) != expected {
+                    wrong += 1;
+                }
+            }
+        }
+        // which the plain determinant gets wrong
+        assert!(wrong > 0);
+    }
+
+    #[test]
+    fn clear_cases() {
+        assert_eq!(orientation(&(0., 0.), &(1., 0.), &(0., 1.)), Orientation::Left);
+        assert_eq!(orientation(&(0., 0.), &(0., 1.), &(1., 0.)), Orientation::Right);
+        assert_eq!(orientation(&(0., 0.), &(1., 1.), &(3., 3.)), Orientation::Collinear);
+        assert_eq!(orientation(&(1., 1.), &(1., 1.), &(5., -2.)), Orientation::Collinear);
+        // all on y = x, whatever the rounding of the decimals
+        assert_eq!(orientation(&(0.1, 0.1), &(0.2, 0.2), &(0.3, 0.3)), Orientation::Collinear);
+    }
+
+    #[test]
+    fn integers_at_the_extremes() {
+        let (min, max) = ([i64::MIN, i64::MIN], [i64::MAX, i64::MAX]);
+        assert_eq!(orientation(&min, &max, &[0, 1]), Orientation::Left);
+        assert_eq!(orientation(&min, &max, &[1, 0]), Orientation::Right);
+        assert_eq!(orientation(&min, &max, &[-7, -7]), Orientation::Collinear);
+        assert_eq!(orientation(&max, &min, &[0, 1]), Orientation::Right);
+
+        // (2^64 - 1)^2 doesn't fit in an i128
+        assert_eq!(orientation(&min, &max, &[i64::MIN, i64::MAX]), Orientation::Left);
+        assert_eq!(orientation(&min, &max, &[i64::MAX, i64::MIN]), Orientation::Right);
+
+        let (a, b) = ([i64::MIN, i64::MAX], [i64::MAX, i64::MIN]);
+        assert_eq!(orientation(&a, &b, &[0, -1]), Orientation::Collinear);
+        assert_eq!(orientation(&a, &b, &[0, 0]), Orientation::Left);
+        assert_eq!(orientation(&a, &b, &[-1, -1]), Orientation::Right);
+        assert_eq!(orientation(&[0i64, 0], &[0, 0], &[i64::MAX, 3]), Orientation::Collinear);
+    }
+
+    #[test]
+    fn integers_agree_with_floats() {
+        // small enough to be exact in both
+        for ax in -3i64..3 {
+            for by in -3i64..3 {
+                for cx in -3i64..3 {
+                    let (a, b, c) = ([ax, 1], [2, by], [cx, -2]);
+                    let float = |p: [i64; 2]| [p[0] as f64, p[1] as f64];
+                    assert_eq!(
+                        orientation(&a, &b, &c),
+                        orientation(&float(a), &float(b), &float(c))
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,98 @@
+//! QuickHull: the leftmost and the rightmost point split the rest in two, then each side is
+//! split again at its point farthest from the segment, leaving out what's inside the triangle.
+
+use std::{cmp::Ordering, ops::Range};
+
+use crate::{
+    algorithm::{finish, HullAlgorithm},
+    chain::leftmost,
+    hull::{Collinear, Entry},
+    point::{Coordinate, Coordinates},
+    predicates::{orientation, turn, Orientation},
+};
+
+pub struct QuickHull;
+
+impl HullAlgorithm for QuickHull {
+    const NAME: &'static str = "QuickHull";
+
+    fn hull<P: Coordinates>(points: &[P], collinear: Collinear) -> Vec<usize> {
+        let entries = points.iter().enumerate().map(|(i, p)| ([p.x(), p.y()], i)).collect();
+        finish(points, vertices(entries), collinear)
+    }
+}
+
+// moves the entries `keep` holds for to the front, returning how many there are
+fn partition<T>(entries: &mut [T], keep: impl Fn(&T) -> bool) -> usize {
+    let mut kept = 0;
+    for i in 0..entries.len() {
+        if keep(&entries[i]) {
+            entries.swap(kept, i);
+            kept += 1;
+        }
+    }
+    kept
+}
+
+enum Task<C> {
+    // the entries strictly right of the segment
+    Side(Entry<C>, Entry<C>, Range<usize>),
+    Vertex(Entry<C>),
+}
+
+/// Vertices of the hull from the leftmost, as [`finish`] takes them.
+fn vertices<C: Coordinate>(mut entries: Vec<Entry<C>>) -> Vec<Entry<C>> {
+    let extreme = |order: fn(&[C; 2], &[C; 2]) -> Ordering| {
+        entries.iter().copied().min_by(|(a, i), (b, j)| order(a, b).then(i.cmp(j)))
+    };
+    let (a, b) = match (extreme(leftmost), extreme(|a, b| leftmost(b, a))) {
+        (Some(a), Some(b)) if a.0 != b.0 => (a, b),
+        (Some(a), _) => return vec![a],
+        _ => return vec![],
+    };
+
+    let right = |a: &Entry<C>, b: &Entry<C>| {
+        let (a, b) = (a.0, b.0);
+        move |(p, _): &Entry<C>| orientation(&a, &b, p) == Orientation::Right
+    };
+    let below = partition(&mut entries, right(&a, &b));
+    let above = below + partition(&mut entries[below..], right(&b, &a));
+
+    let mut hull = vec![];
+    let mut tasks =
+        vec![Task::Side(b, a, below..above), Task::Vertex(b), Task::Side(a, b, 0..below)];
+    while let Some(task) = tasks.pop() {
+        let (a, b, range) = match task {
+            Task::Vertex(v) => {
+                hull.push(v);
+                continue;
+            }
+            Task::Side(a, b, range) => (a, b, range),
+        };
+        if range.is_empty() {
+            continue;
+        }
+
+        // farthest from the line, then the rightmost of those, which is at an end of them
+        let side = &mut entries[range.clone()];
+        let &c = side
+            .iter()
+            .min_by(|r, s| match turn(&a.0, &b.0, &s.0, &r.0) {
+                Orientation::Right => Ordering::Less,
+                Orientation::Left => Ordering::Greater,
+                Orientation::Collinear => leftmost(&s.0, &r.0).then(r.1.cmp(&s.1)),
+            })
+            .unwrap();
+
+        let first = partition(side, right(&a, &c));
+        let second = first + partition(&mut side[first..], right(&c, &b));
+        let start = range.start;
+        tasks.push(Task::Side(c, b, start + first..start + second));
+        tasks.push(Task::Vertex(c));
+        tasks.push(Task::Side(a, c, start..start + first));
+    }
+
+    let mut vertices = vec![a];
+    vertices.extend(hull);
+    vertices
+}
@@ -0,0 +1,16 @@
+//! Deterministic inputs shared by the tests.
+
+/// Deterministic values in `[0, 1 << 31)`.
+pub(crate) fn lcg(seed: u64) -> impl FnMut() -> u64 {
+    let mut state = seed;
+    move || {
+        state = state.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+        state >> 33
+    }
+}
+
+/// Deterministic values in `[0, 1)`, multiples of 2^-24.
+pub(crate) fn uniform(seed: u64) -> impl FnMut() -> f64 {
+    let mut next = lcg(seed);
+    move || (next() >> 7) as f64 / (1u64 << 24) as f64
+}